@@ -0,0 +1,417 @@
+use core::borrow::Borrow;
+use core::convert::TryFrom;
+use core::hash::{Hash, Hasher};
+use core::{cmp, fmt, ops};
+
+use crate::CapacityError;
+use crate::OsStr;
+use crate::OsStrExt;
+
+/// A fixed-capacity, stack-allocated OS string.
+///
+/// `ArrayOsString<N>` is to [`OsString`] as `[u8; N]` is to `Vec<u8>`: it
+/// stores up to `N` bytes inline and never allocates, so it is available
+/// without the `alloc` feature. It dereferences to [`OsStr`], so all methods
+/// on [`OsStr`] slices are available on it as well.
+///
+/// Appending with [`push`] panics if the result would not fit; use
+/// [`try_push`] to handle running out of capacity.
+///
+/// # Examples
+///
+/// ```
+/// use embedded_ffi::{ArrayOsString, OsStr};
+///
+/// let mut name = ArrayOsString::<16>::new();
+/// name.push("tty");
+/// name.push("S0");
+/// assert_eq!(name, "ttyS0");
+/// assert_eq!(name.as_os_str(), OsStr::new("ttyS0"));
+///
+/// assert!(name.try_push("-this-does-not-fit").is_err());
+/// assert_eq!(name, "ttyS0");
+/// ```
+///
+/// [`OsString`]: crate::OsString
+/// [`push`]: ArrayOsString::push
+/// [`try_push`]: ArrayOsString::try_push
+#[derive(Clone)]
+pub struct ArrayOsString<const N: usize> {
+	buf: [u8; N],
+	len: usize,
+}
+
+impl<const N: usize> ArrayOsString<N> {
+	/// Constructs a new, empty `ArrayOsString`.
+	///
+	/// # Examples
+	///
+	/// ```
+	/// use embedded_ffi::ArrayOsString;
+	///
+	/// let os_string = ArrayOsString::<8>::new();
+	/// assert!(os_string.is_empty());
+	/// ```
+	pub const fn new() -> ArrayOsString<N> {
+		ArrayOsString {
+			buf: [0; N],
+			len: 0,
+		}
+	}
+
+	/// Converts to an [`OsStr`] slice.
+	///
+	/// # Examples
+	///
+	/// ```
+	/// use embedded_ffi::{ArrayOsString, OsStr};
+	/// use std::convert::TryFrom;
+	///
+	/// let os_string = ArrayOsString::<8>::try_from("foo").unwrap();
+	/// assert_eq!(os_string.as_os_str(), OsStr::new("foo"));
+	/// ```
+	pub fn as_os_str(&self) -> &OsStr {
+		self
+	}
+
+	/// Extends the string with the given [`&OsStr`] slice.
+	///
+	/// [`&OsStr`]: OsStr
+	///
+	/// # Panics
+	///
+	/// Panics if the resulting string would exceed the capacity `N`.
+	///
+	/// # Examples
+	///
+	/// ```
+	/// use embedded_ffi::ArrayOsString;
+	///
+	/// let mut os_string = ArrayOsString::<8>::new();
+	/// os_string.push("foo");
+	/// os_string.push("bar");
+	/// assert_eq!(&os_string, "foobar");
+	/// ```
+	pub fn push<T: AsRef<OsStr>>(&mut self, s: T) {
+		self.try_push(s).expect("ArrayOsString capacity exceeded")
+	}
+
+	/// Extends the string with the given [`&OsStr`] slice, if it fits.
+	///
+	/// [`&OsStr`]: OsStr
+	///
+	/// # Errors
+	///
+	/// Returns [`CapacityError`] and leaves the string unchanged if the
+	/// result would exceed the capacity `N`.
+	///
+	/// # Examples
+	///
+	/// ```
+	/// use embedded_ffi::ArrayOsString;
+	///
+	/// let mut os_string = ArrayOsString::<4>::new();
+	/// assert!(os_string.try_push("foo").is_ok());
+	/// assert!(os_string.try_push("bar").is_err());
+	/// assert_eq!(&os_string, "foo");
+	/// ```
+	pub fn try_push<T: AsRef<OsStr>>(&mut self, s: T) -> Result<(), CapacityError> {
+		let bytes = s.as_ref().as_bytes();
+		let end = self.len + bytes.len();
+		if end > N {
+			return Err(CapacityError(()));
+		}
+		self.buf[self.len..end].copy_from_slice(bytes);
+		self.len = end;
+		Ok(())
+	}
+
+	/// Truncates the `ArrayOsString` to zero length.
+	///
+	/// # Examples
+	///
+	/// ```
+	/// use embedded_ffi::ArrayOsString;
+	/// use std::convert::TryFrom;
+	///
+	/// let mut os_string = ArrayOsString::<8>::try_from("foo").unwrap();
+	/// os_string.clear();
+	/// assert_eq!(&os_string, "");
+	/// ```
+	pub fn clear(&mut self) {
+		self.len = 0
+	}
+
+	/// Returns the number of bytes this `ArrayOsString` can hold, which is
+	/// always `N`.
+	///
+	/// # Examples
+	///
+	/// ```
+	/// use embedded_ffi::ArrayOsString;
+	///
+	/// let os_string = ArrayOsString::<10>::new();
+	/// assert_eq!(os_string.capacity(), 10);
+	/// ```
+	pub const fn capacity(&self) -> usize {
+		N
+	}
+
+	/// Returns the number of bytes that can still be pushed before the
+	/// `ArrayOsString` is full.
+	///
+	/// # Examples
+	///
+	/// ```
+	/// use embedded_ffi::ArrayOsString;
+	///
+	/// let mut os_string = ArrayOsString::<10>::new();
+	/// os_string.push("foo");
+	/// assert_eq!(os_string.remaining_capacity(), 7);
+	/// ```
+	pub fn remaining_capacity(&self) -> usize {
+		N - self.len
+	}
+}
+
+impl<const N: usize> TryFrom<&OsStr> for ArrayOsString<N> {
+	type Error = CapacityError;
+
+	/// Copies an [`OsStr`] into a new `ArrayOsString`, failing if it is longer
+	/// than `N` bytes.
+	fn try_from(s: &OsStr) -> Result<ArrayOsString<N>, CapacityError> {
+		let mut os_string = ArrayOsString::new();
+		os_string.try_push(s)?;
+		Ok(os_string)
+	}
+}
+
+impl<const N: usize> TryFrom<&str> for ArrayOsString<N> {
+	type Error = CapacityError;
+
+	/// Copies a [`str`] into a new `ArrayOsString`, failing if it is longer
+	/// than `N` bytes.
+	fn try_from(s: &str) -> Result<ArrayOsString<N>, CapacityError> {
+		ArrayOsString::try_from(OsStr::new(s))
+	}
+}
+
+impl<const N: usize> ops::Deref for ArrayOsString<N> {
+	type Target = OsStr;
+
+	#[inline]
+	fn deref(&self) -> &OsStr {
+		OsStr::from_bytes(&self.buf[..self.len])
+	}
+}
+
+impl<const N: usize> ops::DerefMut for ArrayOsString<N> {
+	#[inline]
+	fn deref_mut(&mut self) -> &mut OsStr {
+		OsStr::from_bytes_mut(&mut self.buf[..self.len])
+	}
+}
+
+impl<const N: usize> Default for ArrayOsString<N> {
+	/// Constructs an empty `ArrayOsString`.
+	#[inline]
+	fn default() -> ArrayOsString<N> {
+		ArrayOsString::new()
+	}
+}
+
+impl<const N: usize> fmt::Debug for ArrayOsString<N> {
+	fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+		fmt::Debug::fmt(&**self, formatter)
+	}
+}
+
+impl<const N: usize> fmt::Write for ArrayOsString<N> {
+	/// Appends the UTF-8 bytes of `s`, so that an `ArrayOsString` can be used
+	/// with [`write!`].
+	///
+	/// Returns [`fmt::Error`] if `s` does not fit into the remaining
+	/// capacity. Pieces written by earlier calls in the same [`write!`] are
+	/// kept in that case.
+	///
+	/// # Examples
+	///
+	/// ```
+	/// use embedded_ffi::ArrayOsString;
+	/// use std::fmt::Write;
+	///
+	/// let mut name = ArrayOsString::<8>::new();
+	/// write!(name, "tty{}", 3).unwrap();
+	/// assert_eq!(name, "tty3");
+	/// assert!(write!(name, "{}", 12345).is_err());
+	/// ```
+	#[inline]
+	fn write_str(&mut self, s: &str) -> fmt::Result {
+		self.try_push(s).map_err(|_| fmt::Error)
+	}
+}
+
+impl<const N: usize> PartialEq for ArrayOsString<N> {
+	fn eq(&self, other: &ArrayOsString<N>) -> bool {
+		**self == **other
+	}
+}
+
+impl<const N: usize> PartialEq<str> for ArrayOsString<N> {
+	fn eq(&self, other: &str) -> bool {
+		&**self == other
+	}
+}
+
+impl<const N: usize> PartialEq<ArrayOsString<N>> for str {
+	fn eq(&self, other: &ArrayOsString<N>) -> bool {
+		&**other == self
+	}
+}
+
+impl<const N: usize> PartialEq<&str> for ArrayOsString<N> {
+	fn eq(&self, other: &&str) -> bool {
+		**self == **other
+	}
+}
+
+impl<const N: usize> PartialEq<ArrayOsString<N>> for &str {
+	fn eq(&self, other: &ArrayOsString<N>) -> bool {
+		**other == **self
+	}
+}
+
+impl<const N: usize> Eq for ArrayOsString<N> {}
+
+impl<const N: usize> PartialOrd for ArrayOsString<N> {
+	#[inline]
+	fn partial_cmp(&self, other: &ArrayOsString<N>) -> Option<cmp::Ordering> {
+		Some(self.cmp(other))
+	}
+}
+
+impl<const N: usize> PartialOrd<str> for ArrayOsString<N> {
+	#[inline]
+	fn partial_cmp(&self, other: &str) -> Option<cmp::Ordering> {
+		(**self).partial_cmp(other)
+	}
+}
+
+impl<const N: usize> Ord for ArrayOsString<N> {
+	#[inline]
+	fn cmp(&self, other: &ArrayOsString<N>) -> cmp::Ordering {
+		(**self).cmp(&**other)
+	}
+}
+
+impl<const N: usize> Hash for ArrayOsString<N> {
+	#[inline]
+	fn hash<H: Hasher>(&self, state: &mut H) {
+		(**self).hash(state)
+	}
+}
+
+macro_rules! impl_cmp {
+	($lhs:ty, $rhs: ty) => {
+		impl<'a, const N: usize> PartialEq<$rhs> for $lhs {
+			#[inline]
+			fn eq(&self, other: &$rhs) -> bool {
+				<OsStr as PartialEq>::eq(self, other)
+			}
+		}
+
+		impl<'a, const N: usize> PartialEq<$lhs> for $rhs {
+			#[inline]
+			fn eq(&self, other: &$lhs) -> bool {
+				<OsStr as PartialEq>::eq(self, other)
+			}
+		}
+
+		impl<'a, const N: usize> PartialOrd<$rhs> for $lhs {
+			#[inline]
+			fn partial_cmp(&self, other: &$rhs) -> Option<cmp::Ordering> {
+				<OsStr as PartialOrd>::partial_cmp(self, other)
+			}
+		}
+
+		impl<'a, const N: usize> PartialOrd<$lhs> for $rhs {
+			#[inline]
+			fn partial_cmp(&self, other: &$lhs) -> Option<cmp::Ordering> {
+				<OsStr as PartialOrd>::partial_cmp(self, other)
+			}
+		}
+	};
+}
+
+impl_cmp!(ArrayOsString<N>, OsStr);
+impl_cmp!(ArrayOsString<N>, &'a OsStr);
+
+impl<const N: usize> Borrow<OsStr> for ArrayOsString<N> {
+	fn borrow(&self) -> &OsStr {
+		self
+	}
+}
+
+impl<const N: usize> AsRef<OsStr> for ArrayOsString<N> {
+	fn as_ref(&self) -> &OsStr {
+		self
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn test_array_os_string_push() {
+		let mut os_string = ArrayOsString::<6>::new();
+		assert_eq!(os_string.capacity(), 6);
+		os_string.push("abc");
+		os_string.push(OsStr::from_bytes(b"\xFF"));
+		assert_eq!(os_string.as_bytes(), b"abc\xFF");
+		assert_eq!(os_string.len(), 4);
+		assert_eq!(os_string.remaining_capacity(), 2);
+
+		assert_eq!(os_string.try_push("def"), Err(CapacityError(())));
+		assert_eq!(os_string.len(), 4);
+		assert_eq!(os_string.try_push("de"), Ok(()));
+		assert_eq!(os_string.remaining_capacity(), 0);
+		assert_eq!(os_string.try_push(""), Ok(()));
+
+		os_string.clear();
+		assert!(os_string.is_empty());
+		assert_eq!(os_string, "");
+	}
+
+	#[test]
+	#[should_panic]
+	fn test_array_os_string_push_overflow() {
+		let mut os_string = ArrayOsString::<2>::new();
+		os_string.push("abc");
+	}
+
+	#[test]
+	fn test_array_os_string_write() {
+		use core::fmt::Write;
+
+		let mut os_string = ArrayOsString::<6>::new();
+		assert!(write!(os_string, "ab{}", 12).is_ok());
+		assert_eq!(os_string, "ab12");
+		assert_eq!(write!(os_string, "{}", "xyz"), Err(fmt::Error));
+		assert_eq!(os_string, "ab12");
+	}
+
+	#[test]
+	fn test_array_os_string_cmp() {
+		let a = ArrayOsString::<8>::try_from("abc").unwrap();
+		let b = ArrayOsString::<8>::try_from("abd").unwrap();
+		assert!(a < b);
+		assert_eq!(a, a.clone());
+		assert_eq!(a, "abc");
+		assert_eq!("abc", a);
+		assert_eq!(a, *OsStr::new("abc"));
+		assert_eq!(OsStr::new("abc"), a);
+		assert!(a < *"abd");
+		assert!(ArrayOsString::<2>::try_from("abc").is_err());
+	}
+}
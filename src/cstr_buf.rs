@@ -0,0 +1,202 @@
+use core::fmt;
+use core::ops;
+
+use crate::CStr;
+use crate::CapacityError;
+use crate::OsStr;
+use crate::OsStrExt;
+
+/// The size of the stack buffer used by [`OsStr::with_cstr`].
+pub const MAX_STACK_CSTR: usize = 256;
+
+/// An error returned when an [`OsStr`] cannot be turned into a nul-terminated
+/// C string.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CStrBufError {
+	/// The string contains a nul byte at the given position, so it cannot
+	/// be represented as a C string.
+	InteriorNul(usize),
+	/// The string and its nul terminator do not fit into the buffer.
+	Capacity(CapacityError),
+}
+
+impl From<CapacityError> for CStrBufError {
+	fn from(err: CapacityError) -> CStrBufError {
+		CStrBufError::Capacity(err)
+	}
+}
+
+impl fmt::Display for CStrBufError {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		match self {
+			CStrBufError::InteriorNul(pos) => {
+				write!(f, "nul byte found in provided data at position: {}", pos)
+			}
+			CStrBufError::Capacity(err) => fmt::Display::fmt(err, f),
+		}
+	}
+}
+
+/// A nul-terminated C string stored in a fixed-size buffer on the stack.
+///
+/// This is the allocation-free counterpart to [`CString`]: it copies an
+/// [`OsStr`] into `N` bytes of inline storage, checks it for interior nul
+/// bytes and appends the terminator, so that it can be handed to C as a
+/// [`CStr`]. The string can hold at most `N - 1` bytes, since one byte is
+/// needed for the terminator.
+///
+/// # Examples
+///
+/// ```
+/// use embedded_ffi::{CStrBuf, OsStr};
+///
+/// let path = OsStr::new("/dev/ttyS0");
+/// let c_path = CStrBuf::<32>::new(path).unwrap();
+/// assert_eq!(c_path.to_bytes_with_nul(), b"/dev/ttyS0\0");
+///
+/// assert!(CStrBuf::<8>::new(path).is_err());
+/// ```
+///
+/// [`CString`]: crate::CString
+#[derive(Clone)]
+pub struct CStrBuf<const N: usize> {
+	buf: [u8; N],
+	// Length of the string, excluding the terminator
+	len: usize,
+}
+
+impl<const N: usize> CStrBuf<N> {
+	/// Copies `s` into a new `CStrBuf` and nul-terminates it.
+	///
+	/// # Errors
+	///
+	/// Returns [`CStrBufError::InteriorNul`] if `s` contains a nul byte, or
+	/// [`CStrBufError::Capacity`] if `s` is `N` bytes or longer.
+	pub fn new<S: AsRef<OsStr> + ?Sized>(s: &S) -> Result<CStrBuf<N>, CStrBufError> {
+		let bytes = s.as_ref().as_bytes();
+		if let Some(pos) = bytes.iter().position(|&b| b == 0) {
+			return Err(CStrBufError::InteriorNul(pos));
+		}
+		if bytes.len() >= N {
+			return Err(CapacityError(()).into());
+		}
+		let mut buf = [0; N];
+		buf[..bytes.len()].copy_from_slice(bytes);
+		Ok(CStrBuf {
+			buf,
+			len: bytes.len(),
+		})
+	}
+
+	/// Copies `s` into a nul-terminated C string and calls `f` with it.
+	///
+	/// The string is copied into `N` bytes of stack storage. If it does not
+	/// fit and the `alloc` feature is enabled, a [`CString`] is allocated
+	/// instead; otherwise [`CStrBufError::Capacity`] is returned.
+	///
+	/// [`CString`]: crate::CString
+	///
+	/// # Errors
+	///
+	/// Returns [`CStrBufError::InteriorNul`] if `s` contains a nul byte.
+	///
+	/// Without the `alloc` feature, returns [`CStrBufError::Capacity`] if `s`
+	/// is `N` bytes or longer, so that it and its nul terminator do not fit
+	/// into the stack buffer. For [`OsStr::with_cstr`] that limit is
+	/// [`MAX_STACK_CSTR`].
+	///
+	/// # Examples
+	///
+	/// ```
+	/// use embedded_ffi::{CStrBuf, OsStr};
+	///
+	/// let len = CStrBuf::<16>::with(OsStr::new("/dev/null"), |c| c.to_bytes().len());
+	/// assert_eq!(len, Ok(9));
+	/// ```
+	pub fn with<S, F, R>(s: &S, f: F) -> Result<R, CStrBufError>
+	where
+		S: AsRef<OsStr> + ?Sized,
+		F: FnOnce(&CStr) -> R,
+	{
+		match CStrBuf::<N>::new(s) {
+			Ok(buf) => Ok(f(&buf)),
+			#[cfg(feature = "alloc")]
+			Err(CStrBufError::Capacity(_)) => s
+				.as_ref()
+				.to_cstring()
+				.map(|c| f(&c))
+				.map_err(|err| CStrBufError::InteriorNul(err.nul_position())),
+			Err(err) => Err(err),
+		}
+	}
+
+	/// Borrows the contents as a [`CStr`].
+	pub fn as_c_str(&self) -> &CStr {
+		unsafe { CStr::from_bytes_with_nul_unchecked(&self.buf[..=self.len]) }
+	}
+
+	/// Borrows the contents as an [`OsStr`], without the nul terminator.
+	pub fn as_os_str(&self) -> &OsStr {
+		OsStr::from_bytes(&self.buf[..self.len])
+	}
+}
+
+impl<const N: usize> ops::Deref for CStrBuf<N> {
+	type Target = CStr;
+
+	#[inline]
+	fn deref(&self) -> &CStr {
+		self.as_c_str()
+	}
+}
+
+impl<const N: usize> AsRef<CStr> for CStrBuf<N> {
+	fn as_ref(&self) -> &CStr {
+		self
+	}
+}
+
+impl<const N: usize> fmt::Debug for CStrBuf<N> {
+	fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+		fmt::Debug::fmt(self.as_c_str(), formatter)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn test_cstr_buf_new() {
+		let buf = CStrBuf::<4>::new("abc").unwrap();
+		assert_eq!(buf.to_bytes_with_nul(), b"abc\0");
+		assert_eq!(buf.as_os_str(), "abc");
+
+		let empty = CStrBuf::<1>::new("").unwrap();
+		assert_eq!(empty.to_bytes_with_nul(), b"\0");
+
+		assert_eq!(
+			CStrBuf::<3>::new("abc").unwrap_err(),
+			CStrBufError::Capacity(CapacityError(()))
+		);
+		assert!(CStrBuf::<0>::new("").is_err());
+		assert_eq!(
+			CStrBuf::<8>::new("a\0b").unwrap_err(),
+			CStrBufError::InteriorNul(1)
+		);
+		assert_eq!(
+			CStrBuf::<2>::new("abc\0").unwrap_err(),
+			CStrBufError::InteriorNul(3)
+		);
+	}
+
+	#[test]
+	fn test_with_cstr_fallback() {
+		let long = OsStr::from_bytes(&[b'x'; MAX_STACK_CSTR + 1]);
+		let result = long.with_cstr(|c| c.to_bytes().len());
+		#[cfg(feature = "alloc")]
+		assert_eq!(result, Ok(MAX_STACK_CSTR + 1));
+		#[cfg(not(feature = "alloc"))]
+		assert_eq!(result, Err(CStrBufError::Capacity(CapacityError(()))));
+	}
+}
@@ -0,0 +1,1269 @@
+//! Single-byte legacy code pages, such as those used by LCD controllers,
+//! old FAT volumes and serial terminals.
+//!
+//! An [`Encoding`] maps each byte to one `char` and back. Decoding an
+//! [`OsStr`] of code page bytes never fails; encoding UTF-8 text into a code
+//! page follows an [`Unmappable`] policy for characters the code page does
+//! not contain.
+//!
+//! The built-in encodings are [`Latin1`], [`Cp437`] and [`Windows1252`].
+//! They use static tables and never allocate.
+//!
+//! An [`EncodedOsStr`], or its owned counterpart [`EncodedOsString`], tags a
+//! string or path with the [`NativeEncoding`] of the platform it comes from,
+//! so that its text conversions decode through that encoding instead of
+//! assuming UTF-8.
+//!
+//! # Examples
+//!
+//! ```
+//! use embedded_ffi::encoding::{Cp437, Encoding, Unmappable};
+//! use embedded_ffi::{OsStr, OsStrExt};
+//!
+//! let raw = OsStr::from_bytes(b"\xC9\xCD\xBB 25\xF8C");
+//! assert!(Cp437.decode(raw).eq("╔═╗ 25°C".chars()));
+//!
+//! let mut buf = [0; 8];
+//! let text = OsStr::new("½ € ±");
+//! let len = Cp437.encode_into(text, Unmappable::Replace(b'?'), &mut buf).unwrap();
+//! assert_eq!(&buf[..len], b"\xAB ? \xF1");
+//! ```
+//!
+//! [`OsStr`]: crate::OsStr
+#[cfg(feature = "alloc")]
+use alloc::borrow::{Cow, ToOwned};
+#[cfg(feature = "alloc")]
+use alloc::string::String;
+#[cfg(feature = "alloc")]
+use alloc::vec::Vec;
+#[cfg(feature = "alloc")]
+use core::borrow::Borrow;
+use core::cmp;
+use core::convert::TryFrom;
+use core::fmt::{self, Write};
+use core::hash::{Hash, Hasher};
+use core::iter::FusedIterator;
+use core::marker::PhantomData;
+#[cfg(feature = "alloc")]
+use core::ops;
+use core::slice;
+use core::str;
+
+use crate::lossy::{decode_unit, fmt_padded};
+use crate::{CapacityError, OsStr, OsStrExt, Path};
+#[cfg(feature = "alloc")]
+use crate::{OsString, OsStringExt, PathBuf};
+
+/// A single-byte character encoding.
+///
+/// Implementors only provide the mapping of single bytes and characters; the
+/// provided methods convert whole strings with it. The trait is dyn
+/// compatible, so an encoding can be chosen at runtime as a
+/// `&dyn Encoding`, which implements `Encoding` itself. Only [`decode`]
+/// needs a sized encoding, so it is called through that implementation, as
+/// in `(&encoding).decode(s)`.
+///
+/// [`decode`]: Encoding::decode
+///
+/// # Examples
+///
+/// ```
+/// use embedded_ffi::encoding::{Cp437, Encoding, Latin1};
+/// use embedded_ffi::{OsStr, OsStrExt};
+///
+/// let use_cp437 = true;
+/// let encoding: &dyn Encoding = if use_cp437 { &Cp437 } else { &Latin1 };
+/// let raw = OsStr::from_bytes(b"\xE1");
+/// assert_eq!((&encoding).decode(raw).next(), Some('\u{DF}'));
+/// ```
+pub trait Encoding {
+	/// Returns the character that `byte` stands for.
+	fn decode_byte(&self, byte: u8) -> char;
+
+	/// Returns the byte that stands for `c`, or `None` if the encoding
+	/// cannot represent `c`.
+	fn encode_char(&self, c: char) -> Option<u8>;
+
+	/// Returns an iterator over the characters of the code page string `s`.
+	fn decode<'a>(&'a self, s: &'a OsStr) -> Decode<'a, Self>
+	where
+		Self: Sized,
+	{
+		Decode {
+			encoding: self,
+			bytes: s.as_bytes().iter(),
+		}
+	}
+
+	/// Decodes the code page string `s` into an [`OsString`] of UTF-8.
+	///
+	/// [`OsString`]: crate::OsString
+	#[cfg(feature = "alloc")]
+	fn decode_to_os_string(&self, s: &OsStr) -> OsString {
+		let mut os_string = OsString::with_capacity(s.len());
+		for &byte in s.as_bytes() {
+			let c = self.decode_byte(byte);
+			os_string.push(c.encode_utf8(&mut [0; 4]));
+		}
+		os_string
+	}
+
+	/// Encodes the UTF-8 string `s` into `buf` and returns the number of
+	/// bytes written.
+	///
+	/// Characters that the encoding cannot represent are handled according
+	/// to `unmappable`. Each invalid UTF-8 sequence in `s` counts as a single
+	/// unmappable character.
+	///
+	/// # Errors
+	///
+	/// Returns [`EncodeError::Unmappable`] if `s` contains an unmappable
+	/// character and `unmappable` is [`Unmappable::Error`], or
+	/// [`EncodeError::Capacity`] if the result does not fit into `buf`. The
+	/// contents of `buf` are unspecified in either case.
+	fn encode_into(
+		&self,
+		s: &OsStr,
+		unmappable: Unmappable,
+		buf: &mut [u8],
+	) -> Result<usize, EncodeError> {
+		let mut len = 0;
+		encode_with(self, s, unmappable, |byte| {
+			*buf.get_mut(len).ok_or(CapacityError(()))? = byte;
+			len += 1;
+			Ok(())
+		})?;
+		Ok(len)
+	}
+
+	/// Encodes the UTF-8 string `s` into an [`OsString`] of code page
+	/// bytes.
+	///
+	/// Characters that the encoding cannot represent are handled like in
+	/// [`encode_into`].
+	///
+	/// [`OsString`]: crate::OsString
+	/// [`encode_into`]: Encoding::encode_into
+	///
+	/// # Errors
+	///
+	/// Returns [`EncodeError::Unmappable`] if `s` contains an unmappable
+	/// character and `unmappable` is [`Unmappable::Error`].
+	#[cfg(feature = "alloc")]
+	fn encode(&self, s: &OsStr, unmappable: Unmappable) -> Result<OsString, EncodeError> {
+		let mut vec = Vec::with_capacity(s.len());
+		encode_with(self, s, unmappable, |byte| {
+			vec.push(byte);
+			Ok(())
+		})?;
+		Ok(OsString::from_vec(vec))
+	}
+}
+
+impl<E: Encoding + ?Sized> Encoding for &E {
+	#[inline]
+	fn decode_byte(&self, byte: u8) -> char {
+		(**self).decode_byte(byte)
+	}
+
+	#[inline]
+	fn encode_char(&self, c: char) -> Option<u8> {
+		(**self).encode_char(c)
+	}
+}
+
+/// Encodes `s` byte by byte, passing each byte to `push`.
+fn encode_with<E, F>(
+	encoding: &E,
+	s: &OsStr,
+	unmappable: Unmappable,
+	mut push: F,
+) -> Result<(), EncodeError>
+where
+	E: Encoding + ?Sized,
+	F: FnMut(u8) -> Result<(), CapacityError>,
+{
+	let mut pos = 0;
+	let unmapped = |pos, push: &mut F| match unmappable {
+		Unmappable::Error => Err(EncodeError::Unmappable(pos)),
+		Unmappable::Replace(byte) => Ok(push(byte)?),
+		Unmappable::Skip => Ok(()),
+	};
+	for chunk in s.utf8_chunks() {
+		for c in chunk.valid().chars() {
+			match encoding.encode_char(c) {
+				Some(byte) => push(byte)?,
+				None => unmapped(pos, &mut push)?,
+			}
+			pos += c.len_utf8();
+		}
+		if !chunk.invalid().is_empty() {
+			unmapped(pos, &mut push)?;
+			pos += chunk.invalid().len();
+		}
+	}
+	Ok(())
+}
+
+/// What to do with a character that an [`Encoding`] cannot represent.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Unmappable {
+	/// Fail with [`EncodeError::Unmappable`].
+	Error,
+	/// Write the given byte instead, such as `b'?'`.
+	Replace(u8),
+	/// Leave the character out.
+	Skip,
+}
+
+/// An error returned when encoding a string into a code page fails.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum EncodeError {
+	/// The string contains a character at the given byte position that the
+	/// encoding cannot represent.
+	Unmappable(usize),
+	/// The encoded string does not fit into the buffer.
+	Capacity(CapacityError),
+}
+
+impl From<CapacityError> for EncodeError {
+	fn from(err: CapacityError) -> EncodeError {
+		EncodeError::Capacity(err)
+	}
+}
+
+impl fmt::Display for EncodeError {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		match self {
+			EncodeError::Unmappable(pos) => {
+				write!(f, "unmappable character found at position: {}", pos)
+			}
+			EncodeError::Capacity(err) => fmt::Display::fmt(err, f),
+		}
+	}
+}
+
+/// An iterator over the characters of a code page string.
+///
+/// This `struct` is created by the [`decode`] method on [`Encoding`].
+///
+/// [`decode`]: Encoding::decode
+pub struct Decode<'a, E: ?Sized> {
+	encoding: &'a E,
+	bytes: slice::Iter<'a, u8>,
+}
+
+impl<E: ?Sized> Clone for Decode<'_, E> {
+	fn clone(&self) -> Self {
+		Decode {
+			encoding: self.encoding,
+			bytes: self.bytes.clone(),
+		}
+	}
+}
+
+impl<E: ?Sized> fmt::Debug for Decode<'_, E> {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		f.debug_struct("Decode")
+			.field("bytes", &self.bytes.as_slice())
+			.finish_non_exhaustive()
+	}
+}
+
+impl<E: Encoding + ?Sized> Iterator for Decode<'_, E> {
+	type Item = char;
+
+	#[inline]
+	fn next(&mut self) -> Option<char> {
+		self.bytes.next().map(|&b| self.encoding.decode_byte(b))
+	}
+
+	#[inline]
+	fn size_hint(&self) -> (usize, Option<usize>) {
+		self.bytes.size_hint()
+	}
+}
+
+impl<E: Encoding + ?Sized> DoubleEndedIterator for Decode<'_, E> {
+	#[inline]
+	fn next_back(&mut self) -> Option<char> {
+		self.bytes
+			.next_back()
+			.map(|&b| self.encoding.decode_byte(b))
+	}
+}
+
+impl<E: Encoding + ?Sized> ExactSizeIterator for Decode<'_, E> {}
+
+impl<E: Encoding + ?Sized> FusedIterator for Decode<'_, E> {}
+
+/// ISO 8859-1, which maps each byte to the code point of the same value.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct Latin1;
+
+impl Encoding for Latin1 {
+	#[inline]
+	fn decode_byte(&self, byte: u8) -> char {
+		char::from(byte)
+	}
+
+	#[inline]
+	fn encode_char(&self, c: char) -> Option<u8> {
+		u8::try_from(c).ok()
+	}
+}
+
+/// Code page 437, the character set of the original IBM PC, which is still
+/// found in many character LCDs and in FAT short file names.
+///
+/// The bytes below `0x80` are decoded as ASCII, including the control
+/// characters.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct Cp437;
+
+impl Encoding for Cp437 {
+	#[inline]
+	fn decode_byte(&self, byte: u8) -> char {
+		match byte {
+			0x00..=0x7F => char::from(byte),
+			_ => CP437_HIGH[usize::from(byte - 0x80)],
+		}
+	}
+
+	fn encode_char(&self, c: char) -> Option<u8> {
+		if c.is_ascii() {
+			return Some(c as u8);
+		}
+		let pos = CP437_HIGH.iter().position(|&high| high == c)?;
+		Some(0x80 + pos as u8)
+	}
+}
+
+/// Windows-1252, the Western European ANSI code page of Windows.
+///
+/// It matches [`Latin1`] except for the range `0x80..=0x9F`. The five bytes
+/// in that range that Windows-1252 leaves undefined are decoded as the C1
+/// control characters of the same value, like the WHATWG Encoding Standard
+/// does.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct Windows1252;
+
+impl Encoding for Windows1252 {
+	#[inline]
+	fn decode_byte(&self, byte: u8) -> char {
+		match byte {
+			0x80..=0x9F => WINDOWS_1252_C1[usize::from(byte - 0x80)],
+			_ => char::from(byte),
+		}
+	}
+
+	fn encode_char(&self, c: char) -> Option<u8> {
+		match u8::try_from(c) {
+			Ok(byte @ 0x00..=0x7F) | Ok(byte @ 0xA0..=0xFF) => Some(byte),
+			_ => {
+				let pos = WINDOWS_1252_C1.iter().position(|&c1| c1 == c)?;
+				Some(0x80 + pos as u8)
+			}
+		}
+	}
+}
+
+// https://www.unicode.org/Public/MAPPINGS/VENDORS/MICSFT/PC/CP437.TXT
+#[rustfmt::skip]
+static CP437_HIGH: [char; 128] = [
+	'\u{C7}',   '\u{FC}',   '\u{E9}',   '\u{E2}',   '\u{E4}',   '\u{E0}',   '\u{E5}',   '\u{E7}',
+	'\u{EA}',   '\u{EB}',   '\u{E8}',   '\u{EF}',   '\u{EE}',   '\u{EC}',   '\u{C4}',   '\u{C5}',   // 0x8F
+	'\u{C9}',   '\u{E6}',   '\u{C6}',   '\u{F4}',   '\u{F6}',   '\u{F2}',   '\u{FB}',   '\u{F9}',
+	'\u{FF}',   '\u{D6}',   '\u{DC}',   '\u{A2}',   '\u{A3}',   '\u{A5}',   '\u{20A7}', '\u{192}',  // 0x9F
+	'\u{E1}',   '\u{ED}',   '\u{F3}',   '\u{FA}',   '\u{F1}',   '\u{D1}',   '\u{AA}',   '\u{BA}',
+	'\u{BF}',   '\u{2310}', '\u{AC}',   '\u{BD}',   '\u{BC}',   '\u{A1}',   '\u{AB}',   '\u{BB}',   // 0xAF
+	'\u{2591}', '\u{2592}', '\u{2593}', '\u{2502}', '\u{2524}', '\u{2561}', '\u{2562}', '\u{2556}',
+	'\u{2555}', '\u{2563}', '\u{2551}', '\u{2557}', '\u{255D}', '\u{255C}', '\u{255B}', '\u{2510}', // 0xBF
+	'\u{2514}', '\u{2534}', '\u{252C}', '\u{251C}', '\u{2500}', '\u{253C}', '\u{255E}', '\u{255F}',
+	'\u{255A}', '\u{2554}', '\u{2569}', '\u{2566}', '\u{2560}', '\u{2550}', '\u{256C}', '\u{2567}', // 0xCF
+	'\u{2568}', '\u{2564}', '\u{2565}', '\u{2559}', '\u{2558}', '\u{2552}', '\u{2553}', '\u{256B}',
+	'\u{256A}', '\u{2518}', '\u{250C}', '\u{2588}', '\u{2584}', '\u{258C}', '\u{2590}', '\u{2580}', // 0xDF
+	'\u{3B1}',  '\u{DF}',   '\u{393}',  '\u{3C0}',  '\u{3A3}',  '\u{3C3}',  '\u{B5}',   '\u{3C4}',
+	'\u{3A6}',  '\u{398}',  '\u{3A9}',  '\u{3B4}',  '\u{221E}', '\u{3C6}',  '\u{3B5}',  '\u{2229}', // 0xEF
+	'\u{2261}', '\u{B1}',   '\u{2265}', '\u{2264}', '\u{2320}', '\u{2321}', '\u{F7}',   '\u{2248}',
+	'\u{B0}',   '\u{2219}', '\u{B7}',   '\u{221A}', '\u{207F}', '\u{B2}',   '\u{25A0}', '\u{A0}',   // 0xFF
+];
+
+// https://encoding.spec.whatwg.org/index-windows-1252.txt
+#[rustfmt::skip]
+static WINDOWS_1252_C1: [char; 32] = [
+	'\u{20AC}', '\u{81}',   '\u{201A}', '\u{192}',  '\u{201E}', '\u{2026}', '\u{2020}', '\u{2021}',
+	'\u{2C6}',  '\u{2030}', '\u{160}',  '\u{2039}', '\u{152}',  '\u{8D}',   '\u{17D}',  '\u{8F}',   // 0x8F
+	'\u{90}',   '\u{2018}', '\u{2019}', '\u{201C}', '\u{201D}', '\u{2022}', '\u{2013}', '\u{2014}',
+	'\u{2DC}',  '\u{2122}', '\u{161}',  '\u{203A}', '\u{153}',  '\u{9D}',   '\u{17E}',  '\u{178}',  // 0x9F
+];
+
+/// The native encoding of the bytes in an [`EncodedOsStr`].
+///
+/// This is implemented by [`Utf8`], the default, and by the built-in
+/// single-byte encodings [`Latin1`], [`Cp437`] and [`Windows1252`]. Other
+/// encodings, including multi-byte ones such as Shift-JIS, can be supported
+/// by implementing it for a marker type.
+pub trait NativeEncoding {
+	/// Decodes the character at the start of `bytes`, which is not empty.
+	///
+	/// Returns the character together with the number of bytes it takes up,
+	/// or `Err(len)` with the length of an invalid sequence, which is
+	/// replaced by a single [`U+FFFD REPLACEMENT CHARACTER`][U+FFFD].
+	///
+	/// Either length must be at least 1 and at most `bytes.len()`.
+	/// [`EncodedChars`] clamps a length outside of that range into it, so
+	/// that it neither stalls nor panics.
+	///
+	/// [U+FFFD]: core::char::REPLACEMENT_CHARACTER
+	fn decode_char(bytes: &[u8]) -> Result<(char, usize), usize>;
+}
+
+/// UTF-8, the default encoding of an [`EncodedOsStr`].
+///
+/// Invalid sequences are split up in the same way as by
+/// [`OsStr::utf8_chunks`].
+///
+/// [`OsStr::utf8_chunks`]: crate::OsStr::utf8_chunks
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct Utf8;
+
+impl NativeEncoding for Utf8 {
+	fn decode_char(bytes: &[u8]) -> Result<(char, usize), usize> {
+		let len = decode_unit(bytes)?;
+		let s = unsafe { str::from_utf8_unchecked(&bytes[..len]) };
+		Ok((s.chars().next().unwrap(), len))
+	}
+}
+
+impl NativeEncoding for Latin1 {
+	#[inline]
+	fn decode_char(bytes: &[u8]) -> Result<(char, usize), usize> {
+		Ok((Latin1.decode_byte(bytes[0]), 1))
+	}
+}
+
+impl NativeEncoding for Cp437 {
+	#[inline]
+	fn decode_char(bytes: &[u8]) -> Result<(char, usize), usize> {
+		Ok((Cp437.decode_byte(bytes[0]), 1))
+	}
+}
+
+impl NativeEncoding for Windows1252 {
+	#[inline]
+	fn decode_char(bytes: &[u8]) -> Result<(char, usize), usize> {
+		Ok((Windows1252.decode_byte(bytes[0]), 1))
+	}
+}
+
+/// An [`OsStr`] that is tagged with the native encoding `E` of the platform
+/// it comes from.
+///
+/// Its text conversions, such as [`chars`], [`display`] and
+/// [`to_str_into`], decode through `E`, which defaults to [`Utf8`]. A path
+/// is tagged in the same way, and [`as_path`] gives it back. The owned
+/// counterpart is [`EncodedOsString`]. A crate that targets a platform with
+/// a different native encoding can define an alias for it:
+///
+/// ```
+/// # use embedded_ffi::encoding::Windows1252;
+/// pub type NativeOsStr = embedded_ffi::encoding::EncodedOsStr<Windows1252>;
+/// ```
+///
+/// [`OsStr`]: crate::OsStr
+/// [`chars`]: EncodedOsStr::chars
+/// [`display`]: EncodedOsStr::display
+/// [`to_str_into`]: EncodedOsStr::to_str_into
+/// [`as_path`]: EncodedOsStr::as_path
+///
+/// # Examples
+///
+/// A multi-byte encoding is supported by implementing [`NativeEncoding`].
+/// This one only knows the ASCII, hiragana and half-width katakana ranges
+/// of Shift-JIS:
+///
+/// ```
+/// use embedded_ffi::encoding::{EncodedOsStr, NativeEncoding};
+/// use embedded_ffi::{OsStr, OsStrExt};
+///
+/// struct ShiftJis;
+///
+/// impl NativeEncoding for ShiftJis {
+///     fn decode_char(bytes: &[u8]) -> Result<(char, usize), usize> {
+///         let from = |base: u32, offset: u8| char::from_u32(base + u32::from(offset)).unwrap();
+///         match *bytes {
+///             [b @ 0x00..=0x7F, ..] => Ok((char::from(b), 1)),
+///             [b @ 0xA1..=0xDF, ..] => Ok((from(0xFF61, b - 0xA1), 1)),
+///             [0x82, b @ 0x9F..=0xF1, ..] => Ok((from(0x3041, b - 0x9F), 2)),
+///             _ => Err(1),
+///         }
+///     }
+/// }
+///
+/// let name = EncodedOsStr::<ShiftJis>::new(OsStr::from_bytes(b"\x82\xA0\xB1 1"));
+/// assert_eq!(name.display().to_string(), "あｱ 1");
+///
+/// let invalid = EncodedOsStr::<ShiftJis>::new(OsStr::from_bytes(b"a\xFF"));
+/// assert_eq!(invalid.chars().nth(1), Some(Err(&b"\xFF"[..])));
+/// assert_eq!(invalid.display().to_string(), "a\u{FFFD}");
+/// ```
+#[repr(transparent)]
+pub struct EncodedOsStr<E: NativeEncoding = Utf8> {
+	encoding: PhantomData<fn() -> E>,
+	inner: OsStr,
+}
+
+impl<E: NativeEncoding> EncodedOsStr<E> {
+	/// Tags a string slice with the encoding `E`.
+	pub fn new<S: AsRef<OsStr> + ?Sized>(s: &S) -> &EncodedOsStr<E> {
+		let s: &OsStr = s.as_ref();
+		unsafe { &*(s as *const OsStr as *const EncodedOsStr<E>) }
+	}
+
+	/// Returns the underlying bytes as an untagged [`OsStr`].
+	///
+	/// [`OsStr`]: crate::OsStr
+	pub fn as_os_str(&self) -> &OsStr {
+		&self.inner
+	}
+
+	/// Returns the underlying bytes as an untagged [`Path`].
+	///
+	/// [`Path`]: crate::Path
+	pub fn as_path(&self) -> &Path {
+		Path::new(&self.inner)
+	}
+
+	/// Copies the string into an [`EncodedOsString`] with the same encoding.
+	#[cfg(feature = "alloc")]
+	pub fn to_encoded_os_string(&self) -> EncodedOsString<E> {
+		EncodedOsString::from(self.inner.to_os_string())
+	}
+
+	/// Returns an iterator over the characters of this string.
+	///
+	/// Each invalid sequence is returned as an error holding its bytes.
+	pub fn chars(&self) -> EncodedChars<'_, E> {
+		EncodedChars {
+			bytes: self.inner.as_bytes(),
+			encoding: PhantomData,
+		}
+	}
+
+	/// Decodes the string into `buf` as UTF-8 and returns it.
+	///
+	/// # Errors
+	///
+	/// Returns [`DecodeError::Invalid`] with the byte position of the first
+	/// invalid sequence in `E`, or [`DecodeError::Capacity`] if the decoded
+	/// string does not fit into `buf`. The contents of `buf` are unspecified
+	/// in either case.
+	///
+	/// # Examples
+	///
+	/// ```
+	/// use embedded_ffi::encoding::{Cp437, DecodeError, EncodedOsStr, Utf8};
+	/// use embedded_ffi::{OsStr, OsStrExt};
+	///
+	/// let mut buf = [0; 16];
+	/// let name = EncodedOsStr::<Cp437>::new(OsStr::from_bytes(b"25\xF8C"));
+	/// assert_eq!(name.to_str_into(&mut buf), Ok("25°C"));
+	/// assert!(name.to_str_into(&mut buf[..4]).is_err());
+	///
+	/// let invalid = EncodedOsStr::<Utf8>::new(OsStr::from_bytes(b"25\xF8C"));
+	/// assert_eq!(invalid.to_str_into(&mut buf), Err(DecodeError::Invalid(2)));
+	/// ```
+	pub fn to_str_into<'b>(&self, buf: &'b mut [u8]) -> Result<&'b str, DecodeError> {
+		let mut chars = self.chars();
+		let mut len = 0;
+		loop {
+			let pos = self.inner.len() - chars.bytes.len();
+			let c = match chars.next() {
+				Some(c) => c.map_err(|_| DecodeError::Invalid(pos))?,
+				None => break,
+			};
+			let end = len + c.len_utf8();
+			c.encode_utf8(buf.get_mut(len..end).ok_or(CapacityError(()))?);
+			len = end;
+		}
+		Ok(unsafe { str::from_utf8_unchecked(&buf[..len]) })
+	}
+
+	/// Returns the string as UTF-8, if it is valid in `E`.
+	///
+	/// The string is borrowed if its bytes already are the UTF-8 encoding of
+	/// its characters, which is always the case for [`Utf8`], and for ASCII
+	/// in most encodings.
+	#[cfg(feature = "alloc")]
+	pub fn to_str(&self) -> Option<Cow<'_, str>> {
+		if let Some(s) = self.as_utf8() {
+			return Some(Cow::Borrowed(s));
+		}
+		self.chars()
+			.collect::<Result<String, _>>()
+			.ok()
+			.map(Cow::Owned)
+	}
+
+	/// Converts the string to UTF-8, replacing each invalid sequence with
+	/// [`U+FFFD REPLACEMENT CHARACTER`][U+FFFD].
+	///
+	/// The string is borrowed in the same cases as by [`to_str`].
+	///
+	/// [U+FFFD]: core::char::REPLACEMENT_CHARACTER
+	/// [`to_str`]: EncodedOsStr::to_str
+	#[cfg(feature = "alloc")]
+	pub fn to_string_lossy(&self) -> Cow<'_, str> {
+		if let Some(s) = self.as_utf8() {
+			return Cow::Borrowed(s);
+		}
+		self.chars()
+			.map(|c| c.unwrap_or(char::REPLACEMENT_CHARACTER))
+			.collect::<String>()
+			.into()
+	}
+
+	/// Returns the bytes as a `str` if they are valid UTF-8 and decode to
+	/// the same characters in `E`.
+	#[cfg(feature = "alloc")]
+	fn as_utf8(&self) -> Option<&str> {
+		let s = self.inner.to_str()?;
+		if self.chars().eq(s.chars().map(Ok)) {
+			Some(s)
+		} else {
+			None
+		}
+	}
+
+	/// Returns an object that implements [`Display`] for printing this
+	/// string, replacing each invalid sequence with
+	/// [`U+FFFD REPLACEMENT CHARACTER`][U+FFFD].
+	///
+	/// [`Display`]: fmt::Display
+	/// [U+FFFD]: core::char::REPLACEMENT_CHARACTER
+	pub fn display(&self) -> Display<'_, E> {
+		Display { inner: self }
+	}
+}
+
+impl<E: NativeEncoding> fmt::Debug for EncodedOsStr<E> {
+	/// Formats the decoded characters like a `str`, with the bytes of
+	/// invalid sequences written as `\xNN` escapes.
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		f.write_char('"')?;
+		for c in self.chars() {
+			match c {
+				Ok('\'') => f.write_char('\'')?,
+				Ok(c) => {
+					for c in c.escape_debug() {
+						f.write_char(c)?;
+					}
+				}
+				Err(bytes) => {
+					for b in bytes {
+						write!(f, "\\x{:02X}", b)?;
+					}
+				}
+			}
+		}
+		f.write_char('"')
+	}
+}
+
+impl<E: NativeEncoding> PartialEq for EncodedOsStr<E> {
+	fn eq(&self, other: &EncodedOsStr<E>) -> bool {
+		self.inner == other.inner
+	}
+}
+
+impl<E: NativeEncoding> Eq for EncodedOsStr<E> {}
+
+impl<E: NativeEncoding> PartialOrd for EncodedOsStr<E> {
+	fn partial_cmp(&self, other: &EncodedOsStr<E>) -> Option<cmp::Ordering> {
+		Some(self.cmp(other))
+	}
+}
+
+impl<E: NativeEncoding> Ord for EncodedOsStr<E> {
+	/// Compares the underlying bytes, like [`OsStr`] does.
+	///
+	/// [`OsStr`]: crate::OsStr
+	fn cmp(&self, other: &EncodedOsStr<E>) -> cmp::Ordering {
+		self.inner.cmp(&other.inner)
+	}
+}
+
+impl<E: NativeEncoding> Hash for EncodedOsStr<E> {
+	fn hash<H: Hasher>(&self, state: &mut H) {
+		self.inner.hash(state)
+	}
+}
+
+impl<E: NativeEncoding> Default for &EncodedOsStr<E> {
+	/// Creates an empty `EncodedOsStr`.
+	fn default() -> Self {
+		EncodedOsStr::new("")
+	}
+}
+
+impl<E: NativeEncoding> AsRef<OsStr> for EncodedOsStr<E> {
+	fn as_ref(&self) -> &OsStr {
+		&self.inner
+	}
+}
+
+impl<E: NativeEncoding> AsRef<Path> for EncodedOsStr<E> {
+	fn as_ref(&self) -> &Path {
+		self.as_path()
+	}
+}
+
+#[cfg(feature = "alloc")]
+impl<E: NativeEncoding> ToOwned for EncodedOsStr<E> {
+	type Owned = EncodedOsString<E>;
+	fn to_owned(&self) -> EncodedOsString<E> {
+		self.to_encoded_os_string()
+	}
+}
+
+/// An owned [`OsString`] that is tagged with the native encoding `E` of the
+/// platform it comes from.
+///
+/// This is the owned counterpart of [`EncodedOsStr`], which it dereferences
+/// to, so that all of its text conversions decode through `E` as well. An
+/// [`OsString`] or a [`PathBuf`] is tagged without copying by converting it
+/// with [`From`].
+///
+/// [`OsString`]: crate::OsString
+/// [`PathBuf`]: crate::PathBuf
+///
+/// # Examples
+///
+/// ```
+/// use embedded_ffi::encoding::{Cp437, EncodedOsString};
+/// use embedded_ffi::{OsStringExt, PathBuf};
+///
+/// let path = PathBuf::from(embedded_ffi::OsString::from_vec(b"\xC9\xCD\xBB.TXT".to_vec()));
+/// let name = EncodedOsString::<Cp437>::from(path);
+/// assert_eq!(name.display().to_string(), "╔═╗.TXT");
+/// assert_eq!(name.into_path_buf().extension().unwrap(), "TXT");
+/// ```
+#[cfg(feature = "alloc")]
+pub struct EncodedOsString<E: NativeEncoding = Utf8> {
+	encoding: PhantomData<fn() -> E>,
+	inner: OsString,
+}
+
+#[cfg(feature = "alloc")]
+impl<E: NativeEncoding> EncodedOsString<E> {
+	/// Creates a new empty `EncodedOsString`.
+	pub fn new() -> EncodedOsString<E> {
+		EncodedOsString::from(OsString::new())
+	}
+
+	/// Converts to an [`EncodedOsStr`] slice.
+	pub fn as_encoded_os_str(&self) -> &EncodedOsStr<E> {
+		EncodedOsStr::new(&self.inner)
+	}
+
+	/// Converts the `EncodedOsString` into an untagged [`OsString`] without
+	/// copying or allocating.
+	///
+	/// [`OsString`]: crate::OsString
+	pub fn into_os_string(self) -> OsString {
+		self.inner
+	}
+
+	/// Converts the `EncodedOsString` into an untagged [`PathBuf`] without
+	/// copying or allocating.
+	///
+	/// [`PathBuf`]: crate::PathBuf
+	pub fn into_path_buf(self) -> PathBuf {
+		PathBuf::from(self.inner)
+	}
+}
+
+#[cfg(feature = "alloc")]
+impl<E: NativeEncoding> ops::Deref for EncodedOsString<E> {
+	type Target = EncodedOsStr<E>;
+
+	#[inline]
+	fn deref(&self) -> &EncodedOsStr<E> {
+		self.as_encoded_os_str()
+	}
+}
+
+#[cfg(feature = "alloc")]
+impl<E: NativeEncoding> Clone for EncodedOsString<E> {
+	fn clone(&self) -> Self {
+		EncodedOsString::from(self.inner.clone())
+	}
+}
+
+#[cfg(feature = "alloc")]
+impl<E: NativeEncoding> Default for EncodedOsString<E> {
+	fn default() -> Self {
+		EncodedOsString::new()
+	}
+}
+
+#[cfg(feature = "alloc")]
+impl<E: NativeEncoding> fmt::Debug for EncodedOsString<E> {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		fmt::Debug::fmt(&**self, f)
+	}
+}
+
+#[cfg(feature = "alloc")]
+impl<E: NativeEncoding> From<OsString> for EncodedOsString<E> {
+	/// Tags an [`OsString`] with the encoding `E` without copying or
+	/// allocating.
+	///
+	/// [`OsString`]: crate::OsString
+	fn from(s: OsString) -> EncodedOsString<E> {
+		EncodedOsString {
+			encoding: PhantomData,
+			inner: s,
+		}
+	}
+}
+
+#[cfg(feature = "alloc")]
+impl<E: NativeEncoding> From<PathBuf> for EncodedOsString<E> {
+	/// Tags a [`PathBuf`] with the encoding `E` without copying or
+	/// allocating.
+	///
+	/// [`PathBuf`]: crate::PathBuf
+	fn from(path: PathBuf) -> EncodedOsString<E> {
+		EncodedOsString::from(path.into_os_string())
+	}
+}
+
+#[cfg(feature = "alloc")]
+impl<E: NativeEncoding> From<&EncodedOsStr<E>> for EncodedOsString<E> {
+	fn from(s: &EncodedOsStr<E>) -> EncodedOsString<E> {
+		s.to_encoded_os_string()
+	}
+}
+
+#[cfg(feature = "alloc")]
+impl<E: NativeEncoding> Borrow<EncodedOsStr<E>> for EncodedOsString<E> {
+	fn borrow(&self) -> &EncodedOsStr<E> {
+		self
+	}
+}
+
+#[cfg(feature = "alloc")]
+impl<E: NativeEncoding> PartialEq for EncodedOsString<E> {
+	fn eq(&self, other: &EncodedOsString<E>) -> bool {
+		**self == **other
+	}
+}
+
+#[cfg(feature = "alloc")]
+impl<E: NativeEncoding> Eq for EncodedOsString<E> {}
+
+#[cfg(feature = "alloc")]
+impl<E: NativeEncoding> PartialOrd for EncodedOsString<E> {
+	fn partial_cmp(&self, other: &EncodedOsString<E>) -> Option<cmp::Ordering> {
+		Some(self.cmp(other))
+	}
+}
+
+#[cfg(feature = "alloc")]
+impl<E: NativeEncoding> Ord for EncodedOsString<E> {
+	fn cmp(&self, other: &EncodedOsString<E>) -> cmp::Ordering {
+		(**self).cmp(&**other)
+	}
+}
+
+#[cfg(feature = "alloc")]
+impl<E: NativeEncoding> Hash for EncodedOsString<E> {
+	fn hash<H: Hasher>(&self, state: &mut H) {
+		(**self).hash(state)
+	}
+}
+
+#[cfg(feature = "alloc")]
+impl<E: NativeEncoding> AsRef<EncodedOsStr<E>> for EncodedOsString<E> {
+	fn as_ref(&self) -> &EncodedOsStr<E> {
+		self
+	}
+}
+
+#[cfg(feature = "alloc")]
+impl<E: NativeEncoding> AsRef<OsStr> for EncodedOsString<E> {
+	fn as_ref(&self) -> &OsStr {
+		&self.inner
+	}
+}
+
+#[cfg(feature = "alloc")]
+impl<E: NativeEncoding> AsRef<Path> for EncodedOsString<E> {
+	fn as_ref(&self) -> &Path {
+		Path::new(&self.inner)
+	}
+}
+
+/// An error returned when decoding an [`EncodedOsStr`] into a buffer fails.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DecodeError {
+	/// The string contains a sequence at the given byte position that is
+	/// invalid in its encoding.
+	Invalid(usize),
+	/// The decoded string does not fit into the buffer.
+	Capacity(CapacityError),
+}
+
+impl From<CapacityError> for DecodeError {
+	fn from(err: CapacityError) -> DecodeError {
+		DecodeError::Capacity(err)
+	}
+}
+
+impl fmt::Display for DecodeError {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		match self {
+			DecodeError::Invalid(pos) => {
+				write!(f, "invalid sequence found at position: {}", pos)
+			}
+			DecodeError::Capacity(err) => fmt::Display::fmt(err, f),
+		}
+	}
+}
+
+/// An iterator over the characters of an [`EncodedOsStr`].
+///
+/// This `struct` is created by the [`chars`] method on [`EncodedOsStr`].
+///
+/// [`chars`]: EncodedOsStr::chars
+pub struct EncodedChars<'a, E> {
+	bytes: &'a [u8],
+	encoding: PhantomData<fn() -> E>,
+}
+
+impl<E> Clone for EncodedChars<'_, E> {
+	fn clone(&self) -> Self {
+		EncodedChars {
+			bytes: self.bytes,
+			encoding: PhantomData,
+		}
+	}
+}
+
+impl<E> fmt::Debug for EncodedChars<'_, E> {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		f.debug_struct("EncodedChars")
+			.field("bytes", &self.bytes)
+			.finish_non_exhaustive()
+	}
+}
+
+impl<'a, E: NativeEncoding> Iterator for EncodedChars<'a, E> {
+	type Item = Result<char, &'a [u8]>;
+
+	fn next(&mut self) -> Option<Result<char, &'a [u8]>> {
+		if self.bytes.is_empty() {
+			return None;
+		}
+		let (c, len) = match E::decode_char(self.bytes) {
+			Ok((c, len)) => (Ok(c), len),
+			Err(len) => (Err(()), len),
+		};
+		let len = cmp::max(1, cmp::min(len, self.bytes.len()));
+		let (unit, rest) = self.bytes.split_at(len);
+		self.bytes = rest;
+		Some(c.map_err(|()| unit))
+	}
+}
+
+impl<E: NativeEncoding> FusedIterator for EncodedChars<'_, E> {}
+
+/// Helper struct for printing an [`EncodedOsStr`] with [`format!`] and `{}`.
+///
+/// This `struct` is created by the [`display`] method on [`EncodedOsStr`].
+///
+/// [`format!`]: alloc::format
+/// [`display`]: EncodedOsStr::display
+pub struct Display<'a, E: NativeEncoding> {
+	inner: &'a EncodedOsStr<E>,
+}
+
+impl<E: NativeEncoding> fmt::Debug for Display<'_, E> {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		fmt::Debug::fmt(self.inner, f)
+	}
+}
+
+impl<E: NativeEncoding> fmt::Display for Display<'_, E> {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		let chars = self
+			.inner
+			.chars()
+			.map(|c| c.unwrap_or(char::REPLACEMENT_CHARACTER));
+		if f.width().is_some() || f.precision().is_some() {
+			return fmt_padded(f, chars.clone().count(), |f, len| {
+				for c in chars.take(len) {
+					f.write_char(c)?;
+				}
+				Ok(())
+			});
+		}
+		for c in chars {
+			f.write_char(c)?;
+		}
+		Ok(())
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	use crate::ArrayOsString;
+
+	#[cfg(feature = "alloc")]
+	use alloc::format;
+	fn round_trip(encoding: &dyn Encoding) {
+		for byte in 0..=255 {
+			let c = encoding.decode_byte(byte);
+			assert_eq!(encoding.encode_char(c), Some(byte), "{:?}", c);
+		}
+	}
+
+	#[test]
+	fn test_round_trip() {
+		round_trip(&Latin1);
+		round_trip(&Cp437);
+		round_trip(&Windows1252);
+	}
+
+	fn decode<E: Encoding>(encoding: E, bytes: &[u8]) -> ArrayOsString<64> {
+		let mut output = ArrayOsString::new();
+		for c in encoding.decode(OsStr::from_bytes_const(bytes)) {
+			output.push(c.encode_utf8(&mut [0; 4]));
+		}
+		output
+	}
+
+	#[test]
+	fn test_decode() {
+		let bytes = b"a\x80\x9F\xE9\xFF";
+		assert_eq!(decode(Latin1, bytes), "a\u{80}\u{9F}\u{E9}\u{FF}");
+		assert_eq!(decode(Cp437, bytes), "a\u{C7}\u{192}\u{398}\u{A0}");
+		assert_eq!(decode(Windows1252, bytes), "a\u{20AC}\u{178}\u{E9}\u{FF}");
+		let encoding: &dyn Encoding = &Cp437;
+		assert_eq!(decode(encoding, bytes), "a\u{C7}\u{192}\u{398}\u{A0}");
+
+		let decoded = Cp437.decode(OsStr::from_bytes_const(bytes));
+		assert_eq!(decoded.len(), 5);
+		assert!(decoded
+			.rev()
+			.eq("a\u{C7}\u{192}\u{398}\u{A0}".chars().rev()));
+	}
+
+	#[test]
+	fn test_encode_unmappable() {
+		let text = OsStr::from_bytes_const("a\u{20AC}\u{E9}".as_bytes());
+		let mut buf = [0; 8];
+		assert_eq!(
+			Latin1.encode_into(text, Unmappable::Error, &mut buf),
+			Err(EncodeError::Unmappable(1))
+		);
+		let len = Latin1
+			.encode_into(text, Unmappable::Replace(b'?'), &mut buf)
+			.unwrap();
+		assert_eq!(&buf[..len], b"a?\xE9");
+		let len = Latin1
+			.encode_into(text, Unmappable::Skip, &mut buf)
+			.unwrap();
+		assert_eq!(&buf[..len], b"a\xE9");
+
+		let invalid = OsStr::from_bytes_const(b"a\xE2\x82b\xFF");
+		assert_eq!(
+			Latin1.encode_into(invalid, Unmappable::Error, &mut buf),
+			Err(EncodeError::Unmappable(1))
+		);
+		let len = Latin1
+			.encode_into(invalid, Unmappable::Replace(b'?'), &mut buf)
+			.unwrap();
+		assert_eq!(&buf[..len], b"a?b?");
+		assert_eq!(
+			Latin1.encode_into(invalid, Unmappable::Skip, &mut buf[..1]),
+			Err(EncodeError::Capacity(CapacityError(())))
+		);
+	}
+
+	#[test]
+	fn test_encoded_chars() {
+		let bytes = OsStr::from_bytes_const(b"a\xC3\xA9\xE2\x82b\xFF");
+		let mut chars = EncodedOsStr::<Utf8>::new(bytes).chars();
+		assert_eq!(chars.next(), Some(Ok('a')));
+		assert_eq!(chars.next(), Some(Ok('\u{E9}')));
+		assert_eq!(chars.next(), Some(Err(&b"\xE2\x82"[..])));
+		assert_eq!(chars.next(), Some(Ok('b')));
+		assert_eq!(chars.next(), Some(Err(&b"\xFF"[..])));
+		assert_eq!(chars.next(), None);
+
+		let s = EncodedOsStr::<Cp437>::new(bytes);
+		assert!(s
+			.chars()
+			.map(Result::unwrap)
+			.eq("a\u{251C}\u{2310}\u{393}\u{E9}b\u{A0}".chars()));
+	}
+
+	#[test]
+	fn test_encoded_chars_clamped() {
+		struct Overlong;
+
+		impl NativeEncoding for Overlong {
+			fn decode_char(bytes: &[u8]) -> Result<(char, usize), usize> {
+				match bytes[0] {
+					b'a' => Ok(('a', 0)),
+					_ => Err(8),
+				}
+			}
+		}
+
+		let bytes = OsStr::from_bytes_const(b"aa\xFF\xFE");
+		let mut chars = EncodedOsStr::<Overlong>::new(bytes).chars();
+		assert_eq!(chars.next(), Some(Ok('a')));
+		assert_eq!(chars.next(), Some(Ok('a')));
+		assert_eq!(chars.next(), Some(Err(&b"\xFF\xFE"[..])));
+		assert_eq!(chars.next(), None);
+	}
+
+	fn formatted(args: fmt::Arguments<'_>) -> ArrayOsString<64> {
+		let mut output = ArrayOsString::new();
+		output.write_fmt(args).unwrap();
+		output
+	}
+
+	#[test]
+	fn test_encoded_display() {
+		let bytes = OsStr::from_bytes_const(b"\xE9'\xFF");
+		let utf8: &EncodedOsStr = EncodedOsStr::new(bytes);
+		assert_eq!(
+			formatted(format_args!("{} {:?}", utf8.display(), utf8)),
+			"\u{FFFD}'\u{FFFD} \"\\xE9'\\xFF\""
+		);
+
+		let latin1 = EncodedOsStr::<Latin1>::new(bytes);
+		assert_eq!(
+			formatted(format_args!("{} {:?}", latin1.display(), latin1)),
+			"\u{E9}'\u{FF} \"\u{E9}'\u{FF}\""
+		);
+		assert_eq!(
+			formatted(format_args!("{:-^7}", latin1.display())),
+			"--\u{E9}'\u{FF}--"
+		);
+		assert_eq!(
+			formatted(format_args!("{:>4.2}", latin1.display())),
+			"  \u{E9}'"
+		);
+		assert_eq!(
+			formatted(format_args!("{:<4}|", utf8.display())),
+			"\u{FFFD}'\u{FFFD} |"
+		);
+	}
+
+	#[cfg(feature = "alloc")]
+	#[test]
+	fn test_encoded_to_str() {
+		use alloc::borrow::Cow;
+
+		let ascii = OsStr::new("abc");
+		assert!(matches!(
+			EncodedOsStr::<Utf8>::new(ascii).to_str(),
+			Some(Cow::Borrowed("abc"))
+		));
+		assert!(matches!(
+			EncodedOsStr::<Cp437>::new(ascii).to_str(),
+			Some(Cow::Borrowed("abc"))
+		));
+
+		let e_acute = OsStr::new("\u{E9}");
+		assert_eq!(
+			EncodedOsStr::<Utf8>::new(e_acute).to_str().unwrap(),
+			"\u{E9}"
+		);
+		let latin1 = EncodedOsStr::<Latin1>::new(e_acute);
+		assert!(matches!(latin1.to_str(), Some(Cow::Owned(_))));
+		assert_eq!(latin1.to_str().unwrap(), "\u{C3}\u{A9}");
+
+		let invalid = EncodedOsStr::<Utf8>::new(OsStr::from_bytes(b"a\xFFb"));
+		assert_eq!(invalid.to_str(), None);
+		assert_eq!(invalid.to_string_lossy(), "a\u{FFFD}b");
+		assert_eq!(
+			invalid.to_string_lossy(),
+			invalid.as_os_str().to_string_lossy()
+		);
+	}
+
+	#[test]
+	fn test_encoded_to_str_into() {
+		let mut buf = [0; 8];
+		let bytes = OsStr::from_bytes_const(b"a\xE9\xFF");
+		let latin1 = EncodedOsStr::<Latin1>::new(bytes);
+		assert_eq!(latin1.to_str_into(&mut buf), Ok("a\u{E9}\u{FF}"));
+		assert_eq!(
+			latin1.to_str_into(&mut buf[..4]),
+			Err(DecodeError::Capacity(CapacityError(())))
+		);
+		let utf8 = EncodedOsStr::<Utf8>::new(bytes);
+		assert_eq!(utf8.to_str_into(&mut buf), Err(DecodeError::Invalid(1)));
+		assert_eq!(
+			EncodedOsStr::<Utf8>::new("\u{E9}").to_str_into(&mut buf),
+			Ok("\u{E9}")
+		);
+		assert_eq!(<&EncodedOsStr>::default().to_str_into(&mut []), Ok(""));
+	}
+
+	#[test]
+	fn test_encoded_cmp_path() {
+		let a = EncodedOsStr::<Cp437>::new("dir/a\u{E9}");
+		let b = EncodedOsStr::<Cp437>::new("dir/b");
+		assert!(a < b);
+		assert_eq!(a.cmp(a), cmp::Ordering::Equal);
+		assert_eq!(a.as_path().file_name(), Some(OsStr::new("a\u{E9}")));
+		let path: &Path = b.as_ref();
+		assert_eq!(path, Path::new("dir/b"));
+		assert!(<&EncodedOsStr<Cp437>>::default().as_os_str().is_empty());
+	}
+
+	#[cfg(feature = "alloc")]
+	#[test]
+	fn test_encoded_os_string() {
+		use alloc::borrow::ToOwned;
+
+		let bytes = OsStr::from_bytes_const(b"\xC9\xCD\xBB.TXT");
+		let owned = EncodedOsStr::<Cp437>::new(bytes).to_owned();
+		assert_eq!(
+			format!("{}", owned.display()),
+			"\u{2554}\u{2550}\u{2557}.TXT"
+		);
+		assert_eq!(&*owned, EncodedOsStr::new(bytes));
+		assert_eq!(owned.clone(), owned);
+		assert!(EncodedOsString::<Cp437>::default() < owned);
+
+		let path = owned.into_path_buf();
+		assert_eq!(path.extension(), Some(OsStr::new("TXT")));
+		let tagged = EncodedOsString::<Latin1>::from(path);
+		assert_eq!(tagged.to_string_lossy(), "\u{C9}\u{CD}\u{BB}.TXT");
+		assert_eq!(tagged.into_os_string(), bytes);
+	}
+
+	#[cfg(feature = "alloc")]
+	#[test]
+	fn test_encode_alloc() {
+		let text = OsStr::new("\u{2554}\u{2550}\u{2557}");
+		let encoded = Cp437.encode(text, Unmappable::Error).unwrap();
+		assert_eq!(encoded.as_bytes(), b"\xC9\xCD\xBB");
+		assert_eq!(Cp437.decode_to_os_string(&encoded), text);
+		assert_eq!(
+			Windows1252.encode(OsStr::new("\u{81}\u{100}"), Unmappable::Error),
+			Err(EncodeError::Unmappable(2))
+		);
+	}
+}
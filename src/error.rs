@@ -0,0 +1,15 @@
+use core::fmt;
+
+/// An error indicating that a fixed-size buffer was too small to hold the
+/// result of an operation.
+///
+/// This is returned by operations that write into caller-provided or
+/// stack-allocated storage instead of allocating.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CapacityError(pub(crate) ());
+
+impl fmt::Display for CapacityError {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		f.write_str("insufficient capacity")
+	}
+}
@@ -0,0 +1,308 @@
+use core::fmt::{self, Write};
+
+use crate::lossy::{Utf8Lossy, Utf8LossyChunk};
+
+/// The quoting and escaping rules used by [`OsStr::escape`].
+///
+/// Every mode produces a complete literal, including the surrounding quotes.
+/// Except for [`Json`], the original bytes can be recovered from it.
+///
+/// [`Json`]: EscapeMode::Json
+/// [`OsStr::escape`]: crate::OsStr::escape
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum EscapeMode {
+	/// Rust string literal syntax, as used by the [`Debug`] implementation of
+	/// [`OsStr`]: characters are escaped like in the [`Debug`] output of
+	/// `str` and invalid bytes are written as `\xNN` with uppercase hex digits.
+	///
+	/// [`Debug`]: fmt::Debug
+	/// [`OsStr`]: crate::OsStr
+	Debug,
+	/// C string literal syntax: printable ASCII is kept, `\n`, `\r`, `\t`,
+	/// `"` and `\` use their usual escapes, and every other byte is written
+	/// as a three-digit octal escape `\ooo`.
+	C,
+	/// JSON string syntax, with each invalid sequence replaced by `\ufffd`.
+	Json,
+	/// JSON string syntax, with each invalid byte `0xNN` written as the lone
+	/// surrogate `\udcNN`, like Python's `surrogateescape` error handler.
+	/// Unlike [`Json`], this can be decoded back into the original bytes.
+	///
+	/// [`Json`]: EscapeMode::Json
+	JsonSurrogateEscape,
+	/// A POSIX shell word in single quotes, with `'` written as `'\''`.
+	/// Invalid bytes, control characters (including the C1 controls such as
+	/// U+009B) and the line and paragraph separators U+2028 and U+2029 are
+	/// written as the `\xNN` escapes of their bytes in `$'...'` quotes, which
+	/// are understood by bash, zsh and ksh. This keeps the word on one line
+	/// and keeps control sequences from reaching the terminal.
+	Shell,
+}
+
+/// Helper struct for writing an [`OsStr`] as an escaped string literal.
+///
+/// This `struct` is created by the [`escape`] method on [`OsStr`]; see
+/// [`EscapeMode`] for the supported formats.
+///
+/// [`OsStr`]: crate::OsStr
+/// [`escape`]: crate::OsStr::escape
+#[derive(Clone, Copy)]
+pub struct Escape<'a> {
+	bytes: &'a [u8],
+	mode: EscapeMode,
+}
+
+impl<'a> Escape<'a> {
+	pub(crate) fn new(bytes: &'a [u8], mode: EscapeMode) -> Escape<'a> {
+		Escape { bytes, mode }
+	}
+}
+
+impl fmt::Debug for Escape<'_> {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		f.debug_struct("Escape")
+			.field("mode", &self.mode)
+			.finish_non_exhaustive()
+	}
+}
+
+impl fmt::Display for Escape<'_> {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		let chunks = Utf8Lossy::from_bytes(self.bytes).chunks();
+		match self.mode {
+			EscapeMode::Debug => {
+				f.write_char('"')?;
+				for Utf8LossyChunk { valid, broken } in chunks {
+					write_debug_escaped(f, valid)?;
+					for b in broken {
+						write!(f, "\\x{:02X}", b)?;
+					}
+				}
+				f.write_char('"')
+			}
+			EscapeMode::C => {
+				f.write_char('"')?;
+				for &b in self.bytes {
+					match b {
+						b'"' => f.write_str("\\\"")?,
+						b'\\' => f.write_str("\\\\")?,
+						b'\n' => f.write_str("\\n")?,
+						b'\r' => f.write_str("\\r")?,
+						b'\t' => f.write_str("\\t")?,
+						0x20..=0x7E => f.write_char(b as char)?,
+						_ => write!(f, "\\{:03o}", b)?,
+					}
+				}
+				f.write_char('"')
+			}
+			EscapeMode::Json | EscapeMode::JsonSurrogateEscape => {
+				f.write_char('"')?;
+				for Utf8LossyChunk { valid, broken } in chunks {
+					write_json_escaped(f, valid)?;
+					if self.mode == EscapeMode::Json {
+						if !broken.is_empty() {
+							f.write_str("\\ufffd")?;
+						}
+					} else {
+						for b in broken {
+							write!(f, "\\udc{:02x}", b)?;
+						}
+					}
+				}
+				f.write_char('"')
+			}
+			EscapeMode::Shell => {
+				if self.bytes.is_empty() {
+					return f.write_str("''");
+				}
+				let mut quote = ShellQuote::None;
+				for Utf8LossyChunk { valid, broken } in chunks {
+					let mut rest = valid;
+					while !rest.is_empty() {
+						let start = rest.find(needs_shell_escape).unwrap_or(rest.len());
+						let end = rest[start..]
+							.find(|c| !needs_shell_escape(c))
+							.map_or(rest.len(), |i| start + i);
+						write_shell_quoted(f, &rest[..start], &mut quote)?;
+						write_shell_hex(f, &rest.as_bytes()[start..end], &mut quote)?;
+						rest = &rest[end..];
+					}
+					write_shell_hex(f, broken, &mut quote)?;
+				}
+				if quote != ShellQuote::None {
+					f.write_char('\'')?;
+				}
+				Ok(())
+			}
+		}
+	}
+}
+
+/// Returns whether `c` is written as hex escapes in [`EscapeMode::Shell`].
+fn needs_shell_escape(c: char) -> bool {
+	c.is_control() || c == '\u{2028}' || c == '\u{2029}'
+}
+
+/// The kind of quotes that are open while writing a shell word.
+#[derive(PartialEq)]
+enum ShellQuote {
+	None,
+	/// `'...'`, in which every byte except `'` stands for itself.
+	Single,
+	/// `$'...'`, in which `\xNN` escapes are understood.
+	Ansi,
+}
+
+/// Writes `s` in single quotes, with each `'` written as `\'` outside of
+/// them.
+fn write_shell_quoted(f: &mut fmt::Formatter<'_>, s: &str, quote: &mut ShellQuote) -> fmt::Result {
+	for (i, part) in s.split('\'').enumerate() {
+		if i > 0 {
+			if *quote != ShellQuote::None {
+				f.write_char('\'')?;
+			}
+			f.write_str("\\'")?;
+			*quote = ShellQuote::None;
+		}
+		if !part.is_empty() {
+			match *quote {
+				ShellQuote::Single => {}
+				ShellQuote::Ansi => f.write_str("''")?,
+				ShellQuote::None => f.write_char('\'')?,
+			}
+			*quote = ShellQuote::Single;
+			f.write_str(part)?;
+		}
+	}
+	Ok(())
+}
+
+/// Writes `bytes` as `\xNN` escapes in a new pair of `$'...'` quotes.
+fn write_shell_hex(
+	f: &mut fmt::Formatter<'_>,
+	bytes: &[u8],
+	quote: &mut ShellQuote,
+) -> fmt::Result {
+	if bytes.is_empty() {
+		return Ok(());
+	}
+	if *quote != ShellQuote::None {
+		f.write_char('\'')?;
+	}
+	f.write_str("$'")?;
+	*quote = ShellQuote::Ansi;
+	for b in bytes {
+		write!(f, "\\x{:02X}", b)?;
+	}
+	Ok(())
+}
+
+/// Writes `s` with the characters escaped by [`char::escape_debug`], except
+/// for `'`, which like in the [`Debug`] output of `str` is left as is.
+///
+/// [`Debug`]: fmt::Debug
+fn write_debug_escaped(f: &mut fmt::Formatter<'_>, s: &str) -> fmt::Result {
+	let mut from = 0;
+	for (i, c) in s.char_indices() {
+		let esc = c.escape_debug();
+		// If char needs escaping, flush backlog so far and write, else skip
+		if esc.len() != 1 && c != '\'' {
+			f.write_str(&s[from..i])?;
+			for c in esc {
+				f.write_char(c)?;
+			}
+			from = i + c.len_utf8();
+		}
+	}
+	f.write_str(&s[from..])
+}
+
+/// Writes `s` with the characters that cannot appear in a JSON string
+/// escaped.
+fn write_json_escaped(f: &mut fmt::Formatter<'_>, s: &str) -> fmt::Result {
+	for c in s.chars() {
+		match c {
+			'"' => f.write_str("\\\"")?,
+			'\\' => f.write_str("\\\\")?,
+			'\n' => f.write_str("\\n")?,
+			'\r' => f.write_str("\\r")?,
+			'\t' => f.write_str("\\t")?,
+			'\u{8}' => f.write_str("\\b")?,
+			'\u{c}' => f.write_str("\\f")?,
+			'\0'..='\u{1f}' => write!(f, "\\u{:04x}", c as u32)?,
+			_ => f.write_char(c)?,
+		}
+	}
+	Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::{ArrayOsString, OsStr};
+
+	fn escape(bytes: &[u8], mode: EscapeMode) -> ArrayOsString<64> {
+		let mut output = ArrayOsString::new();
+		write!(output, "{}", OsStr::from_bytes_const(bytes).escape(mode)).unwrap();
+		output
+	}
+
+	#[test]
+	fn test_escape_debug() {
+		let bytes = b"a'\"\n\xC3\xA9\xE2\x82\xFF";
+		assert_eq!(
+			escape(bytes, EscapeMode::Debug),
+			"\"a'\\\"\\n\u{e9}\\xE2\\x82\\xFF\""
+		);
+	}
+
+	#[test]
+	fn test_escape_c() {
+		let bytes = b"a\"\\\n\x01\xC3\xA9";
+		assert_eq!(
+			escape(bytes, EscapeMode::C),
+			"\"a\\\"\\\\\\n\\001\\303\\251\""
+		);
+	}
+
+	#[test]
+	fn test_escape_json() {
+		let bytes = b"a\"\x08\x1F\xC3\xA9\xE2\x82\xFF";
+		assert_eq!(
+			escape(bytes, EscapeMode::Json),
+			"\"a\\\"\\b\\u001f\u{e9}\\ufffd\\ufffd\""
+		);
+		assert_eq!(
+			escape(bytes, EscapeMode::JsonSurrogateEscape),
+			"\"a\\\"\\b\\u001f\u{e9}\\udce2\\udc82\\udcff\""
+		);
+	}
+
+	#[test]
+	fn test_escape_shell() {
+		assert_eq!(escape(b"", EscapeMode::Shell), "''");
+		assert_eq!(escape(b"a b", EscapeMode::Shell), "'a b'");
+		assert_eq!(escape(b"it's", EscapeMode::Shell), "'it'\\''s'");
+		assert_eq!(escape(b"'", EscapeMode::Shell), "\\'");
+		assert_eq!(escape(b"''a", EscapeMode::Shell), "\\'\\''a'");
+		assert_eq!(
+			escape(b"a\xFF\xFEb", EscapeMode::Shell),
+			"'a'$'\\xFF'$'\\xFE''b'"
+		);
+		assert_eq!(escape(b"\xFF", EscapeMode::Shell), "$'\\xFF'");
+	}
+
+	#[test]
+	fn test_escape_shell_control() {
+		assert_eq!(
+			escape(b"a\nb\x1B[0m\x7F", EscapeMode::Shell),
+			"'a'$'\\x0A''b'$'\\x1B''[0m'$'\\x7F'"
+		);
+		assert_eq!(escape(b"\t\xFF", EscapeMode::Shell), "$'\\x09'$'\\xFF'");
+		assert_eq!(escape(b"\r'", EscapeMode::Shell), "$'\\x0D'\\'");
+		assert_eq!(
+			escape("a\u{9B}2J\u{2028}\u{E9}".as_bytes(), EscapeMode::Shell),
+			"'a'$'\\xC2\\x9B''2J'$'\\xE2\\x80\\xA8''\u{E9}'"
+		);
+	}
+}
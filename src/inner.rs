@@ -17,7 +17,7 @@ use core::str;
 
 use crate::lossy::Utf8Lossy;
 use crate::os_str::OsStr;
-use crate::sys_common::AsInner;
+use crate::sys_common::{AsInner, AsInnerMut};
 use crate::sys_common::bytestring::debug_fmt_bytestring;
 
 pub(crate) struct Slice {
@@ -42,12 +42,13 @@ pub mod inner_alloc {
 	use alloc::rc::Rc;
 	use alloc::string::String;
 	use alloc::sync::Arc;
-	use alloc::vec::Vec;
+	use alloc::vec::{self, Vec};
 	use core::fmt;
 	use core::mem;
+	use core::ops::RangeBounds;
 
 	use crate::os_str::OsString;
-	use crate::sys_common::{AsInner, FromInner, IntoInner};
+	use crate::sys_common::{AsInner, AsInnerMut, FromInner, IntoInner};
 
 	use super::Slice;
 
@@ -128,6 +129,10 @@ pub mod inner_alloc {
 			unsafe { &*(&*self.inner as *const [u8] as *const _) }
 		}
 
+		pub fn as_mut_slice(&mut self) -> &mut Slice {
+			Slice::from_u8_slice_mut(&mut self.inner)
+		}
+
 		pub fn into_string(self) -> Result<String, Buf> {
 			String::from_utf8(self.inner).map_err(|p| Buf {
 				inner: p.into_bytes(),
@@ -138,6 +143,41 @@ pub mod inner_alloc {
 			self.inner.extend_from_slice(&s.inner)
 		}
 
+		#[inline]
+		pub fn truncate(&mut self, len: usize) {
+			self.inner.truncate(len)
+		}
+
+		#[inline]
+		pub fn pop(&mut self) -> Option<u8> {
+			self.inner.pop()
+		}
+
+		pub fn insert_slice(&mut self, idx: usize, s: &Slice) {
+			self.replace_range(idx..idx, s)
+		}
+
+		pub fn replace_range<R: RangeBounds<usize>>(&mut self, range: R, s: &Slice) {
+			self.inner.splice(range, s.inner.iter().copied());
+		}
+
+		#[inline]
+		pub fn drain<R: RangeBounds<usize>>(&mut self, range: R) -> vec::Drain<'_, u8> {
+			self.inner.drain(range)
+		}
+
+		#[inline]
+		pub fn retain<F: FnMut(u8) -> bool>(&mut self, mut f: F) {
+			self.inner.retain(|&b| f(b))
+		}
+
+		#[inline]
+		pub fn split_off(&mut self, at: usize) -> Buf {
+			Buf {
+				inner: self.inner.split_off(at),
+			}
+		}
+
 		#[inline]
 		pub fn into_box(self) -> Box<Slice> {
 			unsafe { mem::transmute(self.inner.into_boxed_slice()) }
@@ -179,6 +219,25 @@ pub mod inner_alloc {
 		///
 		/// [`OsString`]: ../../../ffi/struct.OsString.html
 		fn into_vec(self) -> Vec<u8>;
+
+		/// Returns a mutable reference to the underlying byte vector of this
+		/// [`OsString`].
+		///
+		/// Any bytes may be written through the returned vector, since an
+		/// [`OsString`] is an arbitrary sequence of bytes on this platform.
+		///
+		/// [`OsString`]: crate::OsString
+		///
+		/// # Examples
+		///
+		/// ```
+		/// use embedded_ffi::{OsString, OsStringExt};
+		///
+		/// let mut os_string = OsString::from("foo");
+		/// os_string.as_mut_vec().extend_from_slice(b"\xFF");
+		/// assert_eq!(os_string.into_vec(), b"foo\xFF");
+		/// ```
+		fn as_mut_vec(&mut self) -> &mut Vec<u8>;
 	}
 
 	impl OsStringExt for OsString {
@@ -188,20 +247,30 @@ pub mod inner_alloc {
 		fn into_vec(self) -> Vec<u8> {
 			self.into_inner().inner
 		}
+		fn as_mut_vec(&mut self) -> &mut Vec<u8> {
+			&mut self.as_inner_mut().inner
+		}
 	}
 }
 
 impl Slice {
-	fn from_u8_slice(s: &[u8]) -> &Slice {
+	pub const fn from_u8_slice(s: &[u8]) -> &Slice {
 		unsafe { &*(s as *const [u8] as *const _) }
 	}
 
-	pub fn from_str(s: &str) -> &Slice {
+	pub fn from_u8_slice_mut(s: &mut [u8]) -> &mut Slice {
+		unsafe { &mut *(s as *mut [u8] as *mut _) }
+	}
+
+	pub const fn from_str(s: &str) -> &Slice {
 		Slice::from_u8_slice(s.as_bytes())
 	}
 
-	pub fn to_str(&self) -> Option<&str> {
-		str::from_utf8(&self.inner).ok()
+	pub const fn to_str(&self) -> Option<&str> {
+		match str::from_utf8(&self.inner) {
+			Ok(s) => Some(s),
+			Err(_) => None,
+		}
 	}
 
 	#[cfg(feature = "alloc")]
@@ -261,6 +330,37 @@ pub trait OsStrExt {
 	///
 	/// [`OsStr`]: ../../../ffi/struct.OsStr.html
 	fn as_bytes(&self) -> &[u8];
+
+	/// Creates a mutable [`OsStr`] from a mutable byte slice.
+	///
+	/// [`OsStr`]: crate::OsStr
+	///
+	/// # Examples
+	///
+	/// ```
+	/// use embedded_ffi::{OsStr, OsStrExt};
+	///
+	/// let mut bytes = *b"foo";
+	/// OsStr::from_bytes_mut(&mut bytes).make_ascii_uppercase();
+	/// assert_eq!(&bytes, b"FOO");
+	/// ```
+	fn from_bytes_mut(slice: &mut [u8]) -> &mut Self;
+
+	/// Gets the underlying mutable byte view of the [`OsStr`] slice.
+	///
+	/// [`OsStr`]: crate::OsStr
+	///
+	/// # Examples
+	///
+	/// ```
+	/// use embedded_ffi::{OsStr, OsStrExt};
+	///
+	/// let mut bytes = *b"foo";
+	/// let os_str = OsStr::from_bytes_mut(&mut bytes);
+	/// os_str.as_bytes_mut()[0] = 0xFF;
+	/// assert_eq!(os_str.as_bytes(), b"\xFFoo");
+	/// ```
+	fn as_bytes_mut(&mut self) -> &mut [u8];
 }
 
 impl OsStrExt for OsStr {
@@ -272,4 +372,12 @@ impl OsStrExt for OsStr {
 	fn as_bytes(&self) -> &[u8] {
 		&self.as_inner().inner
 	}
+	#[inline]
+	fn from_bytes_mut(slice: &mut [u8]) -> &mut OsStr {
+		unsafe { &mut *(slice as *mut [u8] as *mut _) }
+	}
+	#[inline]
+	fn as_bytes_mut(&mut self) -> &mut [u8] {
+		&mut self.as_inner_mut().inner
+	}
 }
@@ -4,8 +4,9 @@
 //! Unix-like conventions. This is mostly copy & pasted from the Rust
 //! standard library.
 //!
-//! Note that OsString and CString require the `alloc` feature enabled
-//! in your Cargo.toml.
+//! Note that OsString, PathBuf and CString require the `alloc` feature
+//! enabled in your Cargo.toml. Without it, [`ArrayOsString`] provides a
+//! fixed-capacity owned string.
 //!
 //! This module provides utilities to handle data across non-Rust
 //! interfaces, like other programming languages and the underlying
@@ -123,22 +124,55 @@
 #[cfg(feature = "alloc")]
 extern crate alloc;
 
+#[doc(hidden)]
+pub use macros::{__cstr_from_bytes_with_nul, __os_str_from_bytes_without_nul};
+
 #[doc(no_inline)]
 pub use cstr_core::CStr;
 #[cfg(feature = "alloc")]
 #[doc(no_inline)]
 pub use cstr_core::CString;
+#[doc(no_inline)]
+pub use cstr_core::FromBytesWithNulError;
+#[cfg(feature = "alloc")]
+#[doc(no_inline)]
+pub use cstr_core::NulError;
 
+pub use array_os_string::ArrayOsString;
+pub use cstr_buf::{CStrBuf, CStrBufError, MAX_STACK_CSTR};
+pub use error::CapacityError;
+pub use escape::{Escape, EscapeMode};
+pub use lossy::{Utf8Chunk, Utf8Chunks};
 #[cfg(feature = "alloc")]
 pub use inner::inner_alloc::OsStringExt;
 pub use inner::OsStrExt;
 pub use os_str::OsStr;
 #[cfg(feature = "alloc")]
-pub use os_str::OsString;
+pub use os_str::{Drain, OsString};
+#[cfg(feature = "alloc")]
+pub use path::PathBuf;
+pub use path::{Path, StripPrefixError, MAIN_SEPARATOR};
+pub use utf8_decoder::{Utf8Decode, Utf8Decoder, Utf8Piece};
+pub use wide::{c_wchar, WCStr, WStr, WideCStr};
+#[cfg(feature = "alloc")]
+pub use wide::WString;
 
+#[macro_use]
+mod macros;
+
+mod array_os_string;
+mod cstr_buf;
+pub mod encoding;
+mod error;
+mod escape;
 mod inner;
 mod lossy;
-mod os_str;
+pub mod os_str;
+pub mod path;
+pub mod pattern;
+pub mod split;
+mod utf8_decoder;
+pub mod wide;
 
 mod sys_common {
 	#[doc(hidden)]
@@ -146,6 +180,11 @@ mod sys_common {
 		fn as_inner(&self) -> &Inner;
 	}
 
+	#[doc(hidden)]
+	pub trait AsInnerMut<Inner: ?Sized> {
+		fn as_inner_mut(&mut self) -> &mut Inner;
+	}
+
 	/// A trait for extracting representations from std types
 	#[doc(hidden)]
 	pub trait IntoInner<Inner> {
@@ -159,27 +198,12 @@ mod sys_common {
 	}
 
 	pub mod bytestring {
-		use core::fmt::{Formatter, Result, Write};
+		use core::fmt::{Display, Formatter, Result};
 
-		use crate::lossy::{Utf8Lossy, Utf8LossyChunk};
+		use crate::escape::{Escape, EscapeMode};
 
 		pub fn debug_fmt_bytestring(slice: &[u8], f: &mut Formatter<'_>) -> Result {
-			// Writes out a valid unicode string with the correct escape sequences
-			fn write_str_escaped(f: &mut Formatter<'_>, s: &str) -> Result {
-				for c in s.chars().flat_map(|c| c.escape_debug()) {
-					f.write_char(c)?
-				}
-				Ok(())
-			}
-
-			f.write_str("\"")?;
-			for Utf8LossyChunk { valid, broken } in Utf8Lossy::from_bytes(slice).chunks() {
-				write_str_escaped(f, valid)?;
-				for b in broken {
-					write!(f, "\\x{:02X}", b)?;
-				}
-			}
-			f.write_str("\"")
+			Display::fmt(&Escape::new(slice, EscapeMode::Debug), f)
 		}
 	}
 }
@@ -1,7 +1,11 @@
 use core::char;
+use core::cmp;
 use core::fmt::{self, Write};
+use core::iter::FusedIterator;
 use core::str as core_str;
 
+use crate::sys_common::bytestring::debug_fmt_bytestring;
+
 /// Lossy UTF-8 string.
 pub struct Utf8Lossy {
 	bytes: [u8],
@@ -21,8 +25,9 @@ impl Utf8Lossy {
 
 /// Iterator over lossy UTF-8 string
 #[allow(missing_debug_implementations)]
+#[derive(Clone)]
 pub struct Utf8LossyChunksIter<'a> {
-	source: &'a [u8],
+	pub(crate) source: &'a [u8],
 }
 
 #[derive(PartialEq, Eq, Debug)]
@@ -35,6 +40,87 @@ pub struct Utf8LossyChunk<'a> {
 	pub broken: &'a [u8],
 }
 
+/// Decodes the UTF-8 sequence at the start of `bytes`.
+///
+/// Returns `Ok(len)` with the length of a valid character, or `Err(len)` with
+/// the length of the maximal invalid prefix of a sequence, which is replaced
+/// by a single replacement character. `bytes` must not be empty.
+pub(crate) fn decode_unit(bytes: &[u8]) -> Result<usize, usize> {
+	const TAG_CONT_U8: u8 = 128;
+	fn safe_get(xs: &[u8], i: usize) -> u8 {
+		*xs.get(i).unwrap_or(&0)
+	}
+
+	let byte = bytes[0];
+	if byte < 128 {
+		return Ok(1);
+	}
+
+	let w = crate::utf8_char_width(byte);
+	let mut i = 1;
+	match w {
+		2 => {
+			if safe_get(bytes, i) & 192 != TAG_CONT_U8 {
+				return Err(i);
+			}
+			i += 1;
+		}
+		3 => {
+			match (byte, safe_get(bytes, i)) {
+				(0xE0, 0xA0..=0xBF) => (),
+				(0xE1..=0xEC, 0x80..=0xBF) => (),
+				(0xED, 0x80..=0x9F) => (),
+				(0xEE..=0xEF, 0x80..=0xBF) => (),
+				_ => return Err(i),
+			}
+			i += 1;
+			if safe_get(bytes, i) & 192 != TAG_CONT_U8 {
+				return Err(i);
+			}
+			i += 1;
+		}
+		4 => {
+			match (byte, safe_get(bytes, i)) {
+				(0xF0, 0x90..=0xBF) => (),
+				(0xF1..=0xF3, 0x80..=0xBF) => (),
+				(0xF4, 0x80..=0x8F) => (),
+				_ => return Err(i),
+			}
+			i += 1;
+			if safe_get(bytes, i) & 192 != TAG_CONT_U8 {
+				return Err(i);
+			}
+			i += 1;
+			if safe_get(bytes, i) & 192 != TAG_CONT_U8 {
+				return Err(i);
+			}
+			i += 1;
+		}
+		_ => return Err(i),
+	}
+	Ok(i)
+}
+
+/// Decodes the UTF-8 sequence at the end of `bytes`, with the same result as
+/// if `bytes` had been decoded from the start with [`decode_unit`].
+///
+/// This works because a sequence never extends over a byte that is not a
+/// continuation byte, and is at most 4 bytes long: the last sequence starts
+/// at the closest such byte, unless that one is followed by stray
+/// continuation bytes, each of which is a sequence of its own.
+pub(crate) fn decode_unit_back(bytes: &[u8]) -> Result<usize, usize> {
+	let end = bytes.len();
+	let start = bytes[end.saturating_sub(4)..]
+		.iter()
+		.rposition(|&b| b & 192 != 128)
+		.map(|i| i + end.saturating_sub(4));
+	match start.map(|start| (start, decode_unit(&bytes[start..]))) {
+		Some((start, Ok(len))) if start + len == end => Ok(len),
+		Some((start, Err(len))) if start + len == end => Err(len),
+		_ => Err(1),
+	}
+}
+
 impl<'a> Iterator for Utf8LossyChunksIter<'a> {
 	type Item = Utf8LossyChunk<'a>;
 
@@ -43,81 +129,17 @@ impl<'a> Iterator for Utf8LossyChunksIter<'a> {
 			return None;
 		}
 
-		const TAG_CONT_U8: u8 = 128;
-		fn safe_get(xs: &[u8], i: usize) -> u8 {
-			*xs.get(i).unwrap_or(&0)
-		}
-
 		let mut i = 0;
 		while i < self.source.len() {
-			let i_ = i;
-
-			let byte = unsafe { *self.source.get_unchecked(i) };
-			i += 1;
-
-			if byte < 128 {
-
-			} else {
-				let w = crate::utf8_char_width(byte);
-
-				macro_rules! error {
-					() => {{
-						unsafe {
-							let r = Utf8LossyChunk {
-								valid: core_str::from_utf8_unchecked(&self.source[0..i_]),
-								broken: &self.source[i_..i],
-							};
-							self.source = &self.source[i..];
-							return Some(r);
-							}
-						}};
-				}
-
-				match w {
-					2 => {
-						if safe_get(self.source, i) & 192 != TAG_CONT_U8 {
-							error!();
-						}
-						i += 1;
-					}
-					3 => {
-						match (byte, safe_get(self.source, i)) {
-							(0xE0, 0xA0..=0xBF) => (),
-							(0xE1..=0xEC, 0x80..=0xBF) => (),
-							(0xED, 0x80..=0x9F) => (),
-							(0xEE..=0xEF, 0x80..=0xBF) => (),
-							_ => {
-								error!();
-							}
-						}
-						i += 1;
-						if safe_get(self.source, i) & 192 != TAG_CONT_U8 {
-							error!();
-						}
-						i += 1;
-					}
-					4 => {
-						match (byte, safe_get(self.source, i)) {
-							(0xF0, 0x90..=0xBF) => (),
-							(0xF1..=0xF3, 0x80..=0xBF) => (),
-							(0xF4, 0x80..=0x8F) => (),
-							_ => {
-								error!();
-							}
-						}
-						i += 1;
-						if safe_get(self.source, i) & 192 != TAG_CONT_U8 {
-							error!();
-						}
-						i += 1;
-						if safe_get(self.source, i) & 192 != TAG_CONT_U8 {
-							error!();
-						}
-						i += 1;
-					}
-					_ => {
-						error!();
-					}
+			match decode_unit(&self.source[i..]) {
+				Ok(len) => i += len,
+				Err(len) => {
+					let r = Utf8LossyChunk {
+						valid: unsafe { core_str::from_utf8_unchecked(&self.source[..i]) },
+						broken: &self.source[i..i + len],
+					};
+					self.source = &self.source[i + len..];
+					return Some(r);
 				}
 			}
 		}
@@ -131,6 +153,125 @@ impl<'a> Iterator for Utf8LossyChunksIter<'a> {
 	}
 }
 
+impl<'a> DoubleEndedIterator for Utf8LossyChunksIter<'a> {
+	fn next_back(&mut self) -> Option<Utf8LossyChunk<'a>> {
+		if self.source.is_empty() {
+			return None;
+		}
+
+		let mut end = self.source.len();
+		let broken_len = match decode_unit_back(self.source) {
+			Ok(_) => 0,
+			Err(len) => len,
+		};
+		end -= broken_len;
+		let broken = &self.source[end..];
+
+		let mut start = end;
+		while start > 0 {
+			match decode_unit_back(&self.source[..start]) {
+				Ok(len) => start -= len,
+				Err(_) => break,
+			}
+		}
+
+		let r = Utf8LossyChunk {
+			valid: unsafe { core_str::from_utf8_unchecked(&self.source[start..end]) },
+			broken,
+		};
+		self.source = &self.source[..start];
+		Some(r)
+	}
+}
+
+impl FusedIterator for Utf8LossyChunksIter<'_> {}
+
+/// An item returned by the [`Utf8Chunks`] iterator.
+///
+/// A `Utf8Chunk` stores a sequence of valid UTF-8 followed by a sequence of
+/// invalid bytes that would be replaced by a single
+/// [`U+FFFD REPLACEMENT CHARACTER`][U+FFFD] in a lossy conversion.
+///
+/// [U+FFFD]: core::char::REPLACEMENT_CHARACTER
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Utf8Chunk<'a> {
+	valid: &'a str,
+	invalid: &'a [u8],
+}
+
+impl<'a> Utf8Chunk<'a> {
+	/// Returns the next validated UTF-8 substring.
+	///
+	/// This substring can be empty at the start of the string or between
+	/// invalid sequences.
+	pub fn valid(&self) -> &'a str {
+		self.valid
+	}
+
+	/// Returns the invalid sequence that caused a failure.
+	///
+	/// The returned slice is at most 3 bytes long and is a maximal invalid
+	/// prefix of a UTF-8 sequence. It is empty only for the last chunk of a
+	/// string that ends in valid UTF-8.
+	pub fn invalid(&self) -> &'a [u8] {
+		self.invalid
+	}
+}
+
+/// An iterator over the valid UTF-8 sequences and invalid bytes of an
+/// [`OsStr`], in the same pieces as a lossy conversion would use.
+///
+/// This `struct` is created by the [`utf8_chunks`] method on [`OsStr`].
+///
+/// [`OsStr`]: crate::OsStr
+/// [`utf8_chunks`]: crate::OsStr::utf8_chunks
+#[derive(Clone)]
+pub struct Utf8Chunks<'a> {
+	iter: Utf8LossyChunksIter<'a>,
+}
+
+impl<'a> Utf8Chunks<'a> {
+	pub(crate) fn new(bytes: &'a [u8]) -> Utf8Chunks<'a> {
+		Utf8Chunks {
+			iter: Utf8Lossy::from_bytes(bytes).chunks(),
+		}
+	}
+}
+
+impl fmt::Debug for Utf8Chunks<'_> {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		f.debug_struct("Utf8Chunks")
+			.field("source", &Utf8Lossy::from_bytes(self.iter.source))
+			.finish()
+	}
+}
+
+impl<'a> Iterator for Utf8Chunks<'a> {
+	type Item = Utf8Chunk<'a>;
+
+	fn next(&mut self) -> Option<Utf8Chunk<'a>> {
+		self.iter
+			.next()
+			.map(|Utf8LossyChunk { valid, broken }| Utf8Chunk {
+				valid,
+				invalid: broken,
+			})
+	}
+}
+
+impl<'a> DoubleEndedIterator for Utf8Chunks<'a> {
+	fn next_back(&mut self) -> Option<Utf8Chunk<'a>> {
+		self.iter
+			.next_back()
+			.map(|Utf8LossyChunk { valid, broken }| Utf8Chunk {
+				valid,
+				invalid: broken,
+			})
+	}
+}
+
+impl FusedIterator for Utf8Chunks<'_> {}
+
 impl fmt::Display for Utf8Lossy {
 	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
 		// If we're the empty string then our iterator won't actually yield
@@ -139,6 +280,12 @@ impl fmt::Display for Utf8Lossy {
 			return "".fmt(f);
 		}
 
+		// Padding and precision have to be applied to the decoded characters,
+		// which needs a separate pass to count them
+		if f.width().is_some() || f.precision().is_some() {
+			return self.fmt_padded(f);
+		}
+
 		for Utf8LossyChunk { valid, broken } in self.chunks() {
 			// If we successfully decoded the whole chunk as a valid string then
 			// we can return a direct formatting of the string which will also
@@ -157,35 +304,133 @@ impl fmt::Display for Utf8Lossy {
 	}
 }
 
-impl fmt::Debug for Utf8Lossy {
-	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-		f.write_char('"')?;
+impl Utf8Lossy {
+	/// Formats the lossy string like `str` would, truncating it to the
+	/// precision and padding it to the width of `f`.
+	fn fmt_padded(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		if let Ok(s) = core_str::from_utf8(&self.bytes) {
+			return fmt::Display::fmt(s, f);
+		}
 
-		for Utf8LossyChunk { valid, broken } in self.chunks() {
-			// Valid part.
-			// Here we partially parse UTF-8 again which is suboptimal.
-			{
-				let mut from = 0;
-				for (i, c) in valid.char_indices() {
-					let esc = c.escape_debug();
-					// If char needs escaping, flush backlog so far and write, else skip
-					if esc.len() != 1 {
-						f.write_str(&valid[from..i])?;
-						for c in esc {
-							f.write_char(c)?;
-						}
-						from = i + c.len_utf8();
-					}
+		let len = self
+			.chunks()
+			.map(|chunk| chunk.valid.chars().count() + !chunk.broken.is_empty() as usize)
+			.sum();
+		fmt_padded(f, len, |f, mut remaining| {
+			for Utf8LossyChunk { valid, broken } in self.chunks() {
+				let (end, count) = match valid.char_indices().nth(remaining) {
+					Some((i, _)) => (i, remaining),
+					None => (valid.len(), valid.chars().count()),
+				};
+				f.write_str(&valid[..end])?;
+				remaining -= count;
+				if !broken.is_empty() && remaining > 0 {
+					f.write_char(char::REPLACEMENT_CHARACTER)?;
+					remaining -= 1;
+				}
+				if remaining == 0 {
+					break;
 				}
-				f.write_str(&valid[from..])?;
 			}
+			Ok(())
+		})
+	}
+}
+
+/// Formats `len` characters like `str` would, truncating them to the
+/// precision and padding them to the width of `f`.
+///
+/// `write` is called with the number of characters left after truncation
+/// and must write exactly that many of them.
+pub(crate) fn fmt_padded<F>(f: &mut fmt::Formatter<'_>, len: usize, write: F) -> fmt::Result
+where
+	F: FnOnce(&mut fmt::Formatter<'_>, usize) -> fmt::Result,
+{
+	let len = f
+		.precision()
+		.map_or(len, |precision| cmp::min(precision, len));
+	let padding = f.width().map_or(0, |width| width.saturating_sub(len));
+	let (pre, post) = match f.align() {
+		Some(fmt::Alignment::Right) => (padding, 0),
+		Some(fmt::Alignment::Center) => (padding / 2, padding - padding / 2),
+		_ => (0, padding),
+	};
+	let fill = f.fill();
+
+	for _ in 0..pre {
+		f.write_char(fill)?;
+	}
+	write(f, len)?;
+	for _ in 0..post {
+		f.write_char(fill)?;
+	}
+	Ok(())
+}
 
-			// Broken parts of string as hex escape.
-			for &b in broken {
-				write!(f, "\\x{:02x}", b)?;
+impl fmt::Debug for Utf8Lossy {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		debug_fmt_bytestring(&self.bytes, f)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn chunks(bytes: &[u8]) -> impl DoubleEndedIterator<Item = (&str, &[u8])> {
+		Utf8Chunks::new(bytes).map(|chunk| (chunk.valid(), chunk.invalid()))
+	}
+
+	#[test]
+	fn test_utf8_chunks() {
+		let bytes = b"a\xC3\xA9\xFF\xE2\x82b\x80\x80\xF0\x9F\x98";
+		let expected: [(&str, &[u8]); 5] = [
+			("a\u{e9}", b"\xFF"),
+			("", b"\xE2\x82"),
+			("b", b"\x80"),
+			("", b"\x80"),
+			("", b"\xF0\x9F\x98"),
+		];
+		assert!(chunks(bytes).eq(expected.iter().copied()));
+		assert!(chunks(bytes).rev().eq(expected.iter().rev().copied()));
+		assert_eq!(chunks(b"").next(), None);
+		assert!(chunks(b"\xFFab")
+			.rev()
+			.eq([("ab", &b""[..]), ("", b"\xFF")]));
+	}
+
+	#[test]
+	fn test_utf8_chunks_back_matches_forward() {
+		let samples: &[&[u8]] = &[
+			b"\xED\xA0\x80",
+			b"\xF4\x90\x80\x80",
+			b"\xC0\x80",
+			b"\x80\xC3",
+			b"\xE0\xA0",
+			b"x\xF0\x9F\x98\x80\x80y",
+			"\u{10ffff}\u{7ff}".as_bytes(),
+		];
+		for bytes in samples {
+			let mut forward: [Option<(&str, &[u8])>; 8] = [None; 8];
+			let mut backward = forward;
+			for (slot, chunk) in forward.iter_mut().zip(chunks(bytes)) {
+				*slot = Some(chunk);
+			}
+			let len = chunks(bytes).count();
+			for (slot, chunk) in backward[..len].iter_mut().rev().zip(chunks(bytes).rev()) {
+				*slot = Some(chunk);
 			}
+			assert_eq!(forward, backward, "{:?}", bytes);
 		}
+	}
 
-		f.write_char('"')
+	#[test]
+	fn test_utf8_chunks_mixed_ends() {
+		let mut iter = chunks(b"a\xFFb\xFFc\xFF");
+		assert_eq!(iter.next_back(), Some(("c", &b"\xFF"[..])));
+		assert_eq!(iter.next(), Some(("a", &b"\xFF"[..])));
+		assert_eq!(iter.next_back(), Some(("b", &b"\xFF"[..])));
+		assert_eq!(iter.next(), None);
+		assert_eq!(iter.next_back(), None);
 	}
 }
@@ -0,0 +1,136 @@
+use core::mem;
+
+use cstr_core::c_char;
+
+use crate::CStr;
+use crate::OsStr;
+
+/// Creates a `&'static` [`CStr`] from a string literal.
+///
+/// A nul terminator is appended to the literal at compile time. The literal
+/// must not contain any nul bytes itself; this is checked during constant
+/// evaluation, so a literal with an interior nul fails to compile instead of
+/// panicking at runtime.
+///
+/// The result is a constant expression, so it can be used to initialize
+/// `static`s and `const`s.
+///
+/// # Examples
+///
+/// ```
+/// use embedded_ffi::{cstr, CStr};
+///
+/// static DEVICES: [&CStr; 2] = [cstr!("/dev/ttyS0"), cstr!("/dev/ttyS1")];
+///
+/// assert_eq!(DEVICES[0].to_bytes_with_nul(), b"/dev/ttyS0\0");
+/// ```
+///
+/// Interior nul bytes are rejected at compile time:
+///
+/// ```compile_fail
+/// use embedded_ffi::cstr;
+///
+/// let bad = cstr!("/dev/\0ttyS0");
+/// ```
+///
+/// [`CStr`]: crate::CStr
+#[macro_export]
+macro_rules! cstr {
+	($s:literal) => {{
+		const CSTR: &$crate::CStr =
+			$crate::__cstr_from_bytes_with_nul(concat!($s, "\0").as_bytes());
+		CSTR
+	}};
+}
+
+/// Creates a `&'static` [`OsStr`] from a string literal.
+///
+/// Like [`cstr!`], the literal is checked for interior nul bytes at compile
+/// time, so every string created with this macro can be passed on to C (e.g.
+/// with [`OsStr::with_cstr`]). The returned [`OsStr`] does not include a nul
+/// terminator.
+///
+/// The result is a constant expression, so it can be used to initialize
+/// `static`s and `const`s.
+///
+/// # Examples
+///
+/// ```
+/// use embedded_ffi::{os_str, OsStr};
+///
+/// static NAMES: [&OsStr; 2] = [os_str!("uart0"), os_str!("uart1")];
+///
+/// assert_eq!(NAMES[1], OsStr::new("uart1"));
+/// ```
+///
+/// ```compile_fail
+/// use embedded_ffi::os_str;
+///
+/// let bad = os_str!("uart\0");
+/// ```
+///
+/// [`OsStr`]: crate::OsStr
+/// [`OsStr::with_cstr`]: crate::OsStr::with_cstr
+#[macro_export]
+macro_rules! os_str {
+	($s:literal) => {{
+		const OS_STR: &$crate::OsStr =
+			$crate::__os_str_from_bytes_without_nul(concat!($s).as_bytes());
+		OS_STR
+	}};
+}
+
+/// Panics, failing constant evaluation, if `bytes` contains a nul byte.
+const fn assert_no_nul(bytes: &[u8]) {
+	let mut i = 0;
+	while i < bytes.len() {
+		if bytes[i] == 0 {
+			panic!("string literal contains an interior nul byte");
+		}
+		i += 1;
+	}
+}
+
+#[doc(hidden)]
+pub const fn __cstr_from_bytes_with_nul(bytes: &[u8]) -> &CStr {
+	match bytes.split_last() {
+		Some((&0, rest)) => assert_no_nul(rest),
+		_ => panic!("string literal is not nul terminated"),
+	}
+	// SAFETY: `bytes` ends in its only nul byte, as checked above. The cast
+	// mirrors `CStr::from_bytes_with_nul_unchecked`, which is not a `const fn`,
+	// and relies on `CStr` wrapping a bare slice of single-byte `c_char`s,
+	// which the assertion below checks when the crate is built.
+	unsafe { &*(bytes as *const [u8] as *const CStr) }
+}
+
+// Fails the build if `CStr` is no longer a slice of single bytes, as the
+// cast in `__cstr_from_bytes_with_nul` assumes.
+const _: () =
+	assert!(mem::size_of::<&CStr>() == mem::size_of::<&[u8]>() && mem::size_of::<c_char>() == 1);
+
+#[doc(hidden)]
+pub const fn __os_str_from_bytes_without_nul(bytes: &[u8]) -> &OsStr {
+	assert_no_nul(bytes);
+	OsStr::from_bytes_const(bytes)
+}
+
+#[cfg(test)]
+mod tests {
+	use crate::OsStrExt;
+
+	#[test]
+	fn test_cstr_macro() {
+		let c_str = cstr!("abc");
+		assert_eq!(c_str.to_bytes_with_nul(), b"abc\0");
+		assert_eq!(cstr!("").to_bytes_with_nul(), b"\0");
+		assert_eq!(cstr!("\u{e9}").to_bytes(), "\u{e9}".as_bytes());
+	}
+
+	#[test]
+	fn test_os_str_macro() {
+		const NAME: &crate::OsStr = os_str!("abc");
+		assert_eq!(NAME.as_bytes(), b"abc");
+		assert!(os_str!("").is_empty());
+	}
+}
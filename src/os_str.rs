@@ -1,8 +1,23 @@
+//! Borrowed and owned strings in the platform's native byte representation.
+//!
+//! [`OsStr`] and [`OsString`] are re-exported at the crate root. This module
+//! also holds their helper types, such as [`Display`], which is kept out of
+//! the crate root so that it does not shadow [`core::fmt::Display`].
+//!
+//! [`OsString`]: crate::OsString
+use crate::escape::{Escape, EscapeMode};
 #[cfg(feature = "alloc")]
 use crate::inner::inner_alloc::Buf;
 use crate::inner::Slice;
+use crate::lossy::Utf8Chunks;
+use crate::pattern::Pattern;
+use crate::split::{Lines, RSplit, RSplitN, Split, SplitAsciiWhitespace, SplitN, SplitTerminator};
 #[allow(unused_imports)]
-use crate::sys_common::{AsInner, FromInner, IntoInner};
+use crate::sys_common::{AsInner, AsInnerMut, FromInner, IntoInner};
+use crate::wide::DecodeWtf8;
+use crate::{CStr, CStrBuf, CStrBufError, CapacityError, FromBytesWithNulError, MAX_STACK_CSTR};
+#[cfg(feature = "alloc")]
+use crate::{CString, NulError};
 #[cfg(feature = "alloc")]
 use alloc::borrow::Cow;
 #[cfg(feature = "alloc")]
@@ -16,8 +31,15 @@ use alloc::string::String;
 #[cfg(feature = "alloc")]
 use alloc::sync::Arc;
 #[cfg(feature = "alloc")]
+use alloc::vec;
+#[cfg(feature = "alloc")]
 use core::borrow::Borrow;
 use core::hash::{Hash, Hasher};
+#[cfg(feature = "alloc")]
+use core::iter::FusedIterator;
+#[cfg(feature = "alloc")]
+use core::ops::RangeBounds;
+use core::slice::SliceIndex;
 use core::str;
 #[allow(unused_imports)]
 use core::{cmp, fmt, ops};
@@ -323,6 +345,176 @@ impl OsString {
 		self.inner.shrink_to(min_capacity)
 	}*/
 
+	/// Shortens this `OsString` to the specified length in bytes.
+	///
+	/// If `new_len` is greater than the string's current length, this has no
+	/// effect.
+	///
+	/// # Examples
+	///
+	/// ```
+	/// use embedded_ffi::OsString;
+	///
+	/// let mut s = OsString::from("hello");
+	/// s.truncate(2);
+	/// assert_eq!(s, "he");
+	/// ```
+	pub fn truncate(&mut self, new_len: usize) {
+		self.inner.truncate(new_len)
+	}
+
+	/// Removes the last byte from this `OsString` and returns it.
+	///
+	/// Returns [`None`] if the string is empty.
+	///
+	/// # Examples
+	///
+	/// ```
+	/// use embedded_ffi::OsString;
+	///
+	/// let mut s = OsString::from("ab");
+	/// assert_eq!(s.pop(), Some(b'b'));
+	/// assert_eq!(s.pop(), Some(b'a'));
+	/// assert_eq!(s.pop(), None);
+	/// ```
+	pub fn pop(&mut self) -> Option<u8> {
+		self.inner.pop()
+	}
+
+	/// Inserts an [`OsStr`] slice into this `OsString` at a byte position.
+	///
+	/// # Panics
+	///
+	/// Panics if `idx` is larger than the string's length.
+	///
+	/// # Examples
+	///
+	/// ```
+	/// use embedded_ffi::OsString;
+	///
+	/// let mut s = OsString::from("/bin");
+	/// s.insert_os_str(0, "/usr");
+	/// assert_eq!(s, "/usr/bin");
+	/// ```
+	pub fn insert_os_str<T: AsRef<OsStr>>(&mut self, idx: usize, s: T) {
+		assert!(idx <= self.len(), "insertion index is out of bounds");
+		self.inner.insert_slice(idx, &s.as_ref().inner)
+	}
+
+	/// Removes the specified range of bytes from this `OsString`.
+	///
+	/// # Panics
+	///
+	/// Panics if the starting point is greater than the end point or if the
+	/// end point is greater than the length of the string.
+	///
+	/// # Examples
+	///
+	/// ```
+	/// use embedded_ffi::OsString;
+	///
+	/// let mut s = OsString::from("console=ttyS0 quiet");
+	/// s.remove(..14);
+	/// assert_eq!(s, "quiet");
+	/// ```
+	pub fn remove<R: RangeBounds<usize>>(&mut self, range: R) {
+		self.inner.drain(range);
+	}
+
+	/// Removes the specified range of bytes from this `OsString` and returns
+	/// them as an iterator.
+	///
+	/// The range is removed even if the iterator is not consumed until the
+	/// end.
+	///
+	/// # Panics
+	///
+	/// Panics if the starting point is greater than the end point or if the
+	/// end point is greater than the length of the string.
+	///
+	/// # Examples
+	///
+	/// ```
+	/// use embedded_ffi::OsString;
+	///
+	/// let mut s = OsString::from("key=value");
+	/// let key: Vec<u8> = s.drain(..4).collect();
+	/// assert_eq!(key, b"key=");
+	/// assert_eq!(s, "value");
+	/// ```
+	pub fn drain<R: RangeBounds<usize>>(&mut self, range: R) -> Drain<'_> {
+		Drain {
+			iter: self.inner.drain(range),
+		}
+	}
+
+	/// Retains only the bytes specified by the predicate.
+	///
+	/// In other words, removes all bytes `b` such that `f(b)` returns
+	/// `false`. The remaining bytes keep their order.
+	///
+	/// # Examples
+	///
+	/// ```
+	/// use embedded_ffi::OsString;
+	///
+	/// let mut s = OsString::from("f_o_o");
+	/// s.retain(|b| b != b'_');
+	/// assert_eq!(s, "foo");
+	/// ```
+	pub fn retain<F: FnMut(u8) -> bool>(&mut self, f: F) {
+		self.inner.retain(f)
+	}
+
+	/// Replaces the specified range of bytes in this `OsString` with the
+	/// given [`OsStr`] slice.
+	///
+	/// The replacement does not need to be the same length as the range.
+	///
+	/// # Panics
+	///
+	/// Panics if the starting point is greater than the end point or if the
+	/// end point is greater than the length of the string.
+	///
+	/// # Examples
+	///
+	/// ```
+	/// use embedded_ffi::OsString;
+	///
+	/// let mut s = OsString::from("/mnt/sd/log.txt");
+	/// s.replace_range(..7, "/data");
+	/// assert_eq!(s, "/data/log.txt");
+	/// ```
+	pub fn replace_range<R: RangeBounds<usize>, T: AsRef<OsStr>>(&mut self, range: R, s: T) {
+		self.inner.replace_range(range, &s.as_ref().inner)
+	}
+
+	/// Splits this `OsString` into two at the given byte index.
+	///
+	/// Returns a newly allocated `OsString` containing the bytes `[at, len)`,
+	/// and leaves the bytes `[0, at)` in `self`.
+	///
+	/// # Panics
+	///
+	/// Panics if `at` is larger than the string's length.
+	///
+	/// # Examples
+	///
+	/// ```
+	/// use embedded_ffi::OsString;
+	///
+	/// let mut s = OsString::from("foo=bar");
+	/// let value = s.split_off(4);
+	/// assert_eq!(s, "foo=");
+	/// assert_eq!(value, "bar");
+	/// ```
+	#[must_use = "use `.truncate()` if you don't need the other half"]
+	pub fn split_off(&mut self, at: usize) -> OsString {
+		OsString {
+			inner: self.inner.split_off(at),
+		}
+	}
+
 	/// Converts this `OsString` into a boxed [`OsStr`].
 	///
 	/// # Examples
@@ -340,6 +532,94 @@ impl OsString {
 	}
 }
 
+/// A draining iterator over the bytes of an [`OsString`].
+///
+/// This `struct` is created by the [`drain`] method on [`OsString`].
+///
+/// [`drain`]: OsString::drain
+#[cfg(feature = "alloc")]
+pub struct Drain<'a> {
+	iter: vec::Drain<'a, u8>,
+}
+
+#[cfg(feature = "alloc")]
+impl<'a> Drain<'a> {
+	/// Returns the remaining bytes of this iterator as an [`OsStr`] slice.
+	///
+	/// # Examples
+	///
+	/// ```
+	/// use embedded_ffi::OsString;
+	///
+	/// let mut s = OsString::from("abc");
+	/// let mut drain = s.drain(..);
+	/// assert_eq!(drain.as_os_str(), "abc");
+	/// drain.next();
+	/// assert_eq!(drain.as_os_str(), "bc");
+	/// ```
+	pub fn as_os_str(&self) -> &OsStr {
+		OsStr::from_inner(Slice::from_u8_slice(self.iter.as_slice()))
+	}
+}
+
+#[cfg(feature = "alloc")]
+impl fmt::Debug for Drain<'_> {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		f.debug_tuple("Drain").field(&self.as_os_str()).finish()
+	}
+}
+
+#[cfg(feature = "alloc")]
+impl Iterator for Drain<'_> {
+	type Item = u8;
+
+	#[inline]
+	fn next(&mut self) -> Option<u8> {
+		self.iter.next()
+	}
+
+	#[inline]
+	fn size_hint(&self) -> (usize, Option<usize>) {
+		self.iter.size_hint()
+	}
+}
+
+#[cfg(feature = "alloc")]
+impl DoubleEndedIterator for Drain<'_> {
+	#[inline]
+	fn next_back(&mut self) -> Option<u8> {
+		self.iter.next_back()
+	}
+}
+
+#[cfg(feature = "alloc")]
+impl ExactSizeIterator for Drain<'_> {}
+
+#[cfg(feature = "alloc")]
+impl FusedIterator for Drain<'_> {}
+
+#[cfg(feature = "alloc")]
+impl fmt::Write for OsString {
+	/// Appends the UTF-8 bytes of `s`, so that an `OsString` can be used with
+	/// [`write!`].
+	///
+	/// # Examples
+	///
+	/// ```
+	/// use embedded_ffi::OsString;
+	/// use std::fmt::Write;
+	///
+	/// let mut name = OsString::from("/dev/");
+	/// write!(name, "tty{}", 3).unwrap();
+	/// assert_eq!(name, "/dev/tty3");
+	/// ```
+	#[inline]
+	fn write_str(&mut self, s: &str) -> fmt::Result {
+		self.push(s);
+		Ok(())
+	}
+}
+
 #[cfg(feature = "alloc")]
 impl From<String> for OsString {
 	/// Converts a [`String`] into a [`OsString`].
@@ -362,12 +642,20 @@ impl<T: ?Sized + AsRef<OsStr>> From<&T> for OsString {
 }
 
 #[cfg(feature = "alloc")]
-impl ops::Index<ops::RangeFull> for OsString {
+impl<I: SliceIndex<[u8], Output = [u8]>> ops::Index<I> for OsString {
 	type Output = OsStr;
 
 	#[inline]
-	fn index(&self, _index: ops::RangeFull) -> &OsStr {
-		OsStr::from_inner(self.inner.as_slice())
+	fn index(&self, index: I) -> &OsStr {
+		&OsStr::from_inner(self.inner.as_slice())[index]
+	}
+}
+
+#[cfg(feature = "alloc")]
+impl<I: SliceIndex<[u8], Output = [u8]>> ops::IndexMut<I> for OsString {
+	#[inline]
+	fn index_mut(&mut self, index: I) -> &mut OsStr {
+		&mut OsStr::from_inner_mut(self.inner.as_mut_slice())[index]
 	}
 }
 
@@ -381,6 +669,14 @@ impl ops::Deref for OsString {
 	}
 }
 
+#[cfg(feature = "alloc")]
+impl ops::DerefMut for OsString {
+	#[inline]
+	fn deref_mut(&mut self) -> &mut OsStr {
+		OsStr::from_inner_mut(self.inner.as_mut_slice())
+	}
+}
+
 #[cfg(feature = "alloc")]
 impl Default for OsString {
 	/// Constructs an empty `OsString`.
@@ -400,7 +696,7 @@ impl fmt::Debug for OsString {
 #[cfg(feature = "alloc")]
 impl PartialEq for OsString {
 	fn eq(&self, other: &OsString) -> bool {
-		self == other
+		**self == **other
 	}
 }
 
@@ -443,19 +739,19 @@ impl PartialOrd for OsString {
 	}
 	#[inline]
 	fn lt(&self, other: &OsString) -> bool {
-		self < other
+		**self < **other
 	}
 	#[inline]
 	fn le(&self, other: &OsString) -> bool {
-		self <= other
+		**self <= **other
 	}
 	#[inline]
 	fn gt(&self, other: &OsString) -> bool {
-		self > other
+		**self > **other
 	}
 	#[inline]
 	fn ge(&self, other: &OsString) -> bool {
-		self >= other
+		**self >= **other
 	}
 }
 
@@ -497,10 +793,55 @@ impl OsStr {
 		s.as_ref()
 	}
 
-	fn from_inner(inner: &Slice) -> &OsStr {
+	/// Wraps a byte slice as an `OsStr` slice in a `const` context.
+	///
+	/// This is the `const` equivalent of [`OsStrExt::from_bytes`], which can
+	/// be used to build `static` tables of `OsStr`s and other compile-time
+	/// values.
+	///
+	/// [`OsStrExt::from_bytes`]: crate::OsStrExt::from_bytes
+	///
+	/// # Examples
+	///
+	/// ```
+	/// use embedded_ffi::OsStr;
+	///
+	/// static NAMES: [&OsStr; 2] = [
+	///     OsStr::from_bytes_const(b"eth0"),
+	///     OsStr::from_bytes_const(b"\xFFwlan0"),
+	/// ];
+	/// assert_eq!(NAMES[0], "eth0");
+	/// assert_eq!(NAMES[1].to_str(), None);
+	/// ```
+	pub const fn from_bytes_const(bytes: &[u8]) -> &OsStr {
+		OsStr::from_inner(Slice::from_u8_slice(bytes))
+	}
+
+	/// Wraps a string slice as an `OsStr` slice in a `const` context.
+	///
+	/// This is the `const` equivalent of [`OsStr::new`].
+	///
+	/// # Examples
+	///
+	/// ```
+	/// use embedded_ffi::OsStr;
+	///
+	/// const DEFAULT_DEVICE: &OsStr = OsStr::from_str_const("/dev/ttyS0");
+	/// const _: () = assert!(DEFAULT_DEVICE.len() == 10);
+	/// assert_eq!(DEFAULT_DEVICE, "/dev/ttyS0");
+	/// ```
+	pub const fn from_str_const(s: &str) -> &OsStr {
+		OsStr::from_inner(Slice::from_str(s))
+	}
+
+	const fn from_inner(inner: &Slice) -> &OsStr {
 		unsafe { &*(inner as *const Slice as *const OsStr) }
 	}
 
+	fn from_inner_mut(inner: &mut Slice) -> &mut OsStr {
+		unsafe { &mut *(inner as *mut Slice as *mut OsStr) }
+	}
+
 	/// Yields a [`&str`] slice if the `OsStr` is valid Unicode.
 	///
 	/// This conversion may entail doing a check for UTF-8 validity.
@@ -515,7 +856,7 @@ impl OsStr {
 	/// let os_str = OsStr::new("foo");
 	/// assert_eq!(os_str.to_str(), Some("foo"));
 	/// ```
-	pub fn to_str(&self) -> Option<&str> {
+	pub const fn to_str(&self) -> Option<&str> {
 		self.inner.to_str()
 	}
 
@@ -587,6 +928,103 @@ impl OsStr {
 		}
 	}
 
+	/// Borrows the `OsStr` as a [`CStr`] without copying.
+	///
+	/// This succeeds only if the `OsStr` already ends with a single nul
+	/// byte and contains no other nul bytes.
+	///
+	/// [`CStr`]: crate::CStr
+	///
+	/// # Errors
+	///
+	/// Returns a [`FromBytesWithNulError`] if the string is not nul
+	/// terminated or contains an interior nul byte.
+	///
+	/// [`FromBytesWithNulError`]: crate::FromBytesWithNulError
+	///
+	/// # Examples
+	///
+	/// ```
+	/// use embedded_ffi::OsStr;
+	///
+	/// let os_str = OsStr::new("/dev/ttyS0\0");
+	/// let c_str = os_str.to_cstr().unwrap();
+	/// assert_eq!(c_str.to_bytes(), b"/dev/ttyS0");
+	///
+	/// assert!(OsStr::new("/dev/ttyS0").to_cstr().is_err());
+	/// assert!(OsStr::new("/dev\0/ttyS0\0").to_cstr().is_err());
+	/// ```
+	pub fn to_cstr(&self) -> Result<&CStr, FromBytesWithNulError> {
+		CStr::from_bytes_with_nul(self.bytes())
+	}
+
+	/// Copies the `OsStr` into a newly allocated, nul-terminated [`CString`].
+	///
+	/// [`CString`]: crate::CString
+	///
+	/// # Errors
+	///
+	/// Returns a [`NulError`] if the string contains a nul byte, which
+	/// reports the position of the first one.
+	///
+	/// [`NulError`]: crate::NulError
+	///
+	/// # Examples
+	///
+	/// ```
+	/// use embedded_ffi::OsStr;
+	///
+	/// let c_string = OsStr::new("/dev/ttyS0").to_cstring().unwrap();
+	/// assert_eq!(c_string.as_bytes_with_nul(), b"/dev/ttyS0\0");
+	///
+	/// let err = OsStr::new("/dev\0/ttyS0").to_cstring().unwrap_err();
+	/// assert_eq!(err.nul_position(), 4);
+	/// ```
+	#[cfg(feature = "alloc")]
+	pub fn to_cstring(&self) -> Result<CString, NulError> {
+		CString::new(self.bytes())
+	}
+
+	/// Calls `f` with a nul-terminated copy of the `OsStr`, without
+	/// allocating if possible.
+	///
+	/// The string is copied into a [`MAX_STACK_CSTR`]-byte buffer on the
+	/// stack. Longer strings are copied into a [`CString`] if the `alloc`
+	/// feature is enabled, and rejected otherwise. Use [`CStrBuf::with`] to
+	/// choose a different buffer size.
+	///
+	/// [`CString`]: crate::CString
+	/// [`MAX_STACK_CSTR`]: crate::MAX_STACK_CSTR
+	/// [`CStrBuf::with`]: crate::CStrBuf::with
+	///
+	/// # Errors
+	///
+	/// Returns [`CStrBufError::InteriorNul`] if the string contains a nul
+	/// byte, or [`CStrBufError::Capacity`] if it is too long for the stack
+	/// buffer and the `alloc` feature is disabled.
+	///
+	/// [`CStrBufError::InteriorNul`]: crate::CStrBufError::InteriorNul
+	/// [`CStrBufError::Capacity`]: crate::CStrBufError::Capacity
+	///
+	/// # Examples
+	///
+	/// ```
+	/// use embedded_ffi::{CStrBufError, OsStr};
+	///
+	/// let name = OsStr::new("eth0");
+	/// let first = name.with_cstr(|c| c.to_bytes_with_nul()[0]);
+	/// assert_eq!(first, Ok(b'e'));
+	///
+	/// let bad = OsStr::new("eth\0");
+	/// assert_eq!(bad.with_cstr(|_| ()), Err(CStrBufError::InteriorNul(3)));
+	/// ```
+	pub fn with_cstr<F, R>(&self, f: F) -> Result<R, CStrBufError>
+	where
+		F: FnOnce(&CStr) -> R,
+	{
+		CStrBuf::<MAX_STACK_CSTR>::with(self, f)
+	}
+
 	/// Checks whether the `OsStr` is empty.
 	///
 	/// # Examples
@@ -600,7 +1038,7 @@ impl OsStr {
 	/// let os_str = OsStr::new("foo");
 	/// assert!(!os_str.is_empty());
 	/// ```
-	pub fn is_empty(&self) -> bool {
+	pub const fn is_empty(&self) -> bool {
 		self.inner.inner.is_empty()
 	}
 
@@ -629,41 +1067,847 @@ impl OsStr {
 	/// let os_str = OsStr::new("foo");
 	/// assert_eq!(os_str.len(), 3);
 	/// ```
-	pub fn len(&self) -> usize {
+	pub const fn len(&self) -> usize {
 		self.inner.inner.len()
 	}
 
-	/// Converts a [`Box`]`<OsStr>` into an [`OsString`] without copying or allocating.
-	#[cfg(feature = "alloc")]
-	pub fn into_os_string(self: Box<OsStr>) -> OsString {
-		let boxed = unsafe { Box::from_raw(Box::into_raw(self) as *mut Slice) };
-		OsString {
-			inner: Buf::from_box(boxed),
-		}
+	/// Returns `true` if the given pattern matches a sub-slice of this
+	/// `OsStr`.
+	///
+	/// The [pattern] can be a `&str`, `&OsStr`, `char`, `u8` or a byte
+	/// predicate, and is matched against the raw bytes of the string.
+	///
+	/// [pattern]: crate::pattern::Pattern
+	///
+	/// # Examples
+	///
+	/// ```
+	/// use embedded_ffi::{OsStr, OsStrExt};
+	///
+	/// let name = OsStr::from_bytes(b"log\xFF.txt");
+	/// assert!(name.contains(".txt"));
+	/// assert!(name.contains(0xFF));
+	/// assert!(!name.contains("log."));
+	/// ```
+	pub fn contains<P: Pattern>(&self, mut pat: P) -> bool {
+		pat.find_in(self.bytes()).is_some()
 	}
 
-	/// Gets the underlying byte representation.
+	/// Returns the byte index of the first match of the pattern in this
+	/// `OsStr`.
 	///
-	/// Note: it is *crucial* that this API is private, to avoid
-	/// revealing the internal, platform-specific encodings.
-	fn bytes(&self) -> &[u8] {
-		unsafe { &*(&self.inner as *const _ as *const [u8]) }
+	/// Returns [`None`] if the pattern doesn't match.
+	///
+	/// # Examples
+	///
+	/// ```
+	/// use embedded_ffi::OsStr;
+	///
+	/// let var = OsStr::new("PATH=/bin:/sbin");
+	/// assert_eq!(var.find('='), Some(4));
+	/// assert_eq!(var.find(|b: u8| b.is_ascii_lowercase()), Some(6));
+	/// assert_eq!(var.find("usr"), None);
+	/// ```
+	pub fn find<P: Pattern>(&self, mut pat: P) -> Option<usize> {
+		pat.find_in(self.bytes()).map(|m| m.start)
 	}
 
-	pub fn display(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
-		fmt::Display::fmt(&self.inner, formatter)
+	/// Returns the byte index of the start of the last match of the pattern
+	/// in this `OsStr`.
+	///
+	/// Returns [`None`] if the pattern doesn't match.
+	///
+	/// # Examples
+	///
+	/// ```
+	/// use embedded_ffi::OsStr;
+	///
+	/// let path = OsStr::new("/bin:/sbin:/usr/bin");
+	/// assert_eq!(path.rfind(':'), Some(10));
+	/// assert_eq!(path.rfind("bin"), Some(16));
+	/// ```
+	pub fn rfind<P: Pattern>(&self, mut pat: P) -> Option<usize> {
+		pat.rfind_in(self.bytes()).map(|m| m.start)
 	}
-}
 
-#[cfg(feature = "alloc")]
-impl From<&OsStr> for Box<OsStr> {
-	fn from(s: &OsStr) -> Box<OsStr> {
-		let rw = Box::into_raw(s.inner.into_box()) as *mut OsStr;
-		unsafe { Box::from_raw(rw) }
+	/// Returns `true` if the given pattern matches a prefix of this `OsStr`.
+	///
+	/// # Examples
+	///
+	/// ```
+	/// use embedded_ffi::OsStr;
+	///
+	/// let device = OsStr::new("ttyUSB0");
+	/// assert!(device.starts_with("tty"));
+	/// assert!(!device.starts_with("USB"));
+	/// ```
+	pub fn starts_with<P: Pattern>(&self, mut pat: P) -> bool {
+		pat.prefix_len(self.bytes()).is_some()
 	}
-}
 
-#[cfg(feature = "alloc")]
+	/// Returns `true` if the given pattern matches a suffix of this `OsStr`.
+	///
+	/// # Examples
+	///
+	/// ```
+	/// use embedded_ffi::OsStr;
+	///
+	/// let device = OsStr::new("ttyUSB0");
+	/// assert!(device.ends_with(|b: u8| b.is_ascii_digit()));
+	/// assert!(!device.ends_with("USB"));
+	/// ```
+	pub fn ends_with<P: Pattern>(&self, mut pat: P) -> bool {
+		pat.suffix_len(self.bytes()).is_some()
+	}
+
+	/// Returns an `OsStr` slice with the prefix removed.
+	///
+	/// If the string starts with the pattern, returns the substring after
+	/// the prefix, wrapped in [`Some`]. Otherwise, returns [`None`].
+	///
+	/// # Examples
+	///
+	/// ```
+	/// use embedded_ffi::OsStr;
+	///
+	/// let var = OsStr::new("HOME=/root");
+	/// assert_eq!(var.strip_prefix("HOME="), Some(OsStr::new("/root")));
+	/// assert_eq!(var.strip_prefix("PATH="), None);
+	/// ```
+	pub fn strip_prefix<P: Pattern>(&self, mut pat: P) -> Option<&OsStr> {
+		let bytes = self.bytes();
+		pat.prefix_len(bytes)
+			.map(|len| OsStr::from_inner(Slice::from_u8_slice(&bytes[len..])))
+	}
+
+	/// Returns an `OsStr` slice with the suffix removed.
+	///
+	/// If the string ends with the pattern, returns the substring before
+	/// the suffix, wrapped in [`Some`]. Otherwise, returns [`None`].
+	///
+	/// # Examples
+	///
+	/// ```
+	/// use embedded_ffi::OsStr;
+	///
+	/// let name = OsStr::new("kernel.img");
+	/// assert_eq!(name.strip_suffix(".img"), Some(OsStr::new("kernel")));
+	/// assert_eq!(name.strip_suffix(".bin"), None);
+	/// ```
+	pub fn strip_suffix<P: Pattern>(&self, mut pat: P) -> Option<&OsStr> {
+		let bytes = self.bytes();
+		pat.suffix_len(bytes)
+			.map(|len| OsStr::from_inner(Slice::from_u8_slice(&bytes[..bytes.len() - len])))
+	}
+
+	/// Returns an iterator over sub-slices of this `OsStr`, separated by
+	/// matches of a pattern.
+	///
+	/// If the pattern is empty, it matches between every byte, as well as at
+	/// the start and end of the string.
+	///
+	/// # Examples
+	///
+	/// ```
+	/// use embedded_ffi::OsStr;
+	///
+	/// let path = OsStr::new("/bin:/sbin::/usr/bin");
+	/// let dirs: Vec<&OsStr> = path.split(':').collect();
+	/// assert_eq!(dirs, ["/bin", "/sbin", "", "/usr/bin"]);
+	/// ```
+	pub fn split<P: Pattern>(&self, pat: P) -> Split<'_, P> {
+		Split::new(self, pat)
+	}
+
+	/// Returns an iterator over sub-slices of this `OsStr`, separated by
+	/// matches of a pattern, starting from the end of the string.
+	///
+	/// # Examples
+	///
+	/// ```
+	/// use embedded_ffi::OsStr;
+	///
+	/// let path = OsStr::new("/bin:/sbin:/usr/bin");
+	/// let dirs: Vec<&OsStr> = path.rsplit(':').collect();
+	/// assert_eq!(dirs, ["/usr/bin", "/sbin", "/bin"]);
+	/// ```
+	pub fn rsplit<P: Pattern>(&self, pat: P) -> RSplit<'_, P> {
+		RSplit::new(self, pat)
+	}
+
+	/// Returns an iterator over at most `n` sub-slices of this `OsStr`,
+	/// separated by matches of a pattern.
+	///
+	/// The last sub-slice returned contains the remainder of the string.
+	///
+	/// # Examples
+	///
+	/// ```
+	/// use embedded_ffi::OsStr;
+	///
+	/// let var = OsStr::new("OPTS=a=1");
+	/// let parts: Vec<&OsStr> = var.splitn(2, '=').collect();
+	/// assert_eq!(parts, ["OPTS", "a=1"]);
+	/// ```
+	pub fn splitn<P: Pattern>(&self, n: usize, pat: P) -> SplitN<'_, P> {
+		SplitN::new(self, pat, n)
+	}
+
+	/// Returns an iterator over at most `n` sub-slices of this `OsStr`,
+	/// separated by matches of a pattern, starting from the end of the
+	/// string.
+	///
+	/// The last sub-slice returned contains the remainder of the string.
+	///
+	/// # Examples
+	///
+	/// ```
+	/// use embedded_ffi::OsStr;
+	///
+	/// let name = OsStr::new("archive.tar.gz");
+	/// let parts: Vec<&OsStr> = name.rsplitn(2, '.').collect();
+	/// assert_eq!(parts, ["gz", "archive.tar"]);
+	/// ```
+	pub fn rsplitn<P: Pattern>(&self, n: usize, pat: P) -> RSplitN<'_, P> {
+		RSplitN::new(self, pat, n)
+	}
+
+	/// Equivalent to [`split`], except that a trailing empty sub-slice is
+	/// skipped.
+	///
+	/// [`split`]: OsStr::split
+	///
+	/// # Examples
+	///
+	/// ```
+	/// use embedded_ffi::OsStr;
+	///
+	/// let list = OsStr::new("a;b;c;");
+	/// let items: Vec<&OsStr> = list.split_terminator(';').collect();
+	/// assert_eq!(items, ["a", "b", "c"]);
+	/// ```
+	pub fn split_terminator<P: Pattern>(&self, pat: P) -> SplitTerminator<'_, P> {
+		SplitTerminator::new(self, pat)
+	}
+
+	/// Splits this `OsStr` on the first match of a pattern, returning the
+	/// parts before and after the match.
+	///
+	/// Returns [`None`] if the pattern doesn't match.
+	///
+	/// # Examples
+	///
+	/// ```
+	/// use embedded_ffi::OsStr;
+	///
+	/// let var = OsStr::new("OPTS=a=1");
+	/// assert_eq!(var.split_once('='), Some((OsStr::new("OPTS"), OsStr::new("a=1"))));
+	/// assert_eq!(var.split_once(':'), None);
+	/// ```
+	pub fn split_once<P: Pattern>(&self, mut pat: P) -> Option<(&OsStr, &OsStr)> {
+		let bytes = self.bytes();
+		pat.find_in(bytes).map(|m| {
+			(
+				OsStr::from_inner(Slice::from_u8_slice(&bytes[..m.start])),
+				OsStr::from_inner(Slice::from_u8_slice(&bytes[m.end..])),
+			)
+		})
+	}
+
+	/// Splits this `OsStr` on the last match of a pattern, returning the
+	/// parts before and after the match.
+	///
+	/// Returns [`None`] if the pattern doesn't match.
+	///
+	/// # Examples
+	///
+	/// ```
+	/// use embedded_ffi::OsStr;
+	///
+	/// let name = OsStr::new("archive.tar.gz");
+	/// assert_eq!(name.rsplit_once('.'), Some((OsStr::new("archive.tar"), OsStr::new("gz"))));
+	/// ```
+	pub fn rsplit_once<P: Pattern>(&self, mut pat: P) -> Option<(&OsStr, &OsStr)> {
+		let bytes = self.bytes();
+		pat.rfind_in(bytes).map(|m| {
+			(
+				OsStr::from_inner(Slice::from_u8_slice(&bytes[..m.start])),
+				OsStr::from_inner(Slice::from_u8_slice(&bytes[m.end..])),
+			)
+		})
+	}
+
+	/// Returns an iterator over the sub-slices of this `OsStr` that are
+	/// separated by any amount of ASCII whitespace.
+	///
+	/// Leading and trailing whitespace is ignored, so the iterator never
+	/// yields empty sub-slices.
+	///
+	/// # Examples
+	///
+	/// ```
+	/// use embedded_ffi::OsStr;
+	///
+	/// let cmdline = OsStr::new("  console=ttyS0\tquiet \n");
+	/// let args: Vec<&OsStr> = cmdline.split_ascii_whitespace().collect();
+	/// assert_eq!(args, ["console=ttyS0", "quiet"]);
+	/// ```
+	pub fn split_ascii_whitespace(&self) -> SplitAsciiWhitespace<'_> {
+		SplitAsciiWhitespace::new(self)
+	}
+
+	/// Returns an iterator over the lines of this `OsStr`.
+	///
+	/// Lines are terminated by either `\n` or `\r\n`, and the terminator is
+	/// not included in the returned sub-slices. A final line ending is
+	/// optional. A `\r` that is not directly followed by `\n` is kept as part
+	/// of the line.
+	///
+	/// # Examples
+	///
+	/// ```
+	/// use embedded_ffi::OsStr;
+	///
+	/// let text = OsStr::new("foo\r\nbar\n\nbaz\n");
+	/// let lines: Vec<&OsStr> = text.lines().collect();
+	/// assert_eq!(lines, ["foo", "bar", "", "baz"]);
+	/// ```
+	pub fn lines(&self) -> Lines<'_> {
+		Lines::new(self)
+	}
+
+	/// Checks that two strings are an ASCII case-insensitive match.
+	///
+	/// Same as `to_ascii_lowercase(a) == to_ascii_lowercase(b)`, but without
+	/// allocating and copying temporaries. Bytes outside the ASCII range are
+	/// compared exactly.
+	///
+	/// # Examples
+	///
+	/// ```
+	/// use embedded_ffi::OsStr;
+	///
+	/// assert!(OsStr::new("Ferris").eq_ignore_ascii_case("FERRIS"));
+	/// assert!(OsStr::new("Ferrös").eq_ignore_ascii_case("FERRöS"));
+	/// assert!(!OsStr::new("Ferrös").eq_ignore_ascii_case("FERRÖS"));
+	/// ```
+	pub fn eq_ignore_ascii_case<S: AsRef<OsStr> + ?Sized>(&self, other: &S) -> bool {
+		self.bytes().eq_ignore_ascii_case(other.as_ref().bytes())
+	}
+
+	/// Returns a copy of this string where each character is mapped to its
+	/// ASCII lower case equivalent.
+	///
+	/// ASCII letters 'A' to 'Z' are mapped to 'a' to 'z', but non-ASCII
+	/// letters are unchanged.
+	///
+	/// To lowercase the value in-place, use [`make_ascii_lowercase`].
+	///
+	/// [`make_ascii_lowercase`]: OsStr::make_ascii_lowercase
+	///
+	/// # Examples
+	///
+	/// ```
+	/// use embedded_ffi::{OsStr, OsString};
+	///
+	/// let s = OsStr::new("Grüße, Jürgen ❤");
+	/// assert_eq!(s.to_ascii_lowercase(), OsString::from("grüße, jürgen ❤"));
+	/// ```
+	#[cfg(feature = "alloc")]
+	pub fn to_ascii_lowercase(&self) -> OsString {
+		OsString {
+			inner: Buf {
+				inner: self.bytes().to_ascii_lowercase(),
+			},
+		}
+	}
+
+	/// Returns a copy of this string where each character is mapped to its
+	/// ASCII upper case equivalent.
+	///
+	/// ASCII letters 'a' to 'z' are mapped to 'A' to 'Z', but non-ASCII
+	/// letters are unchanged.
+	///
+	/// To uppercase the value in-place, use [`make_ascii_uppercase`].
+	///
+	/// [`make_ascii_uppercase`]: OsStr::make_ascii_uppercase
+	///
+	/// # Examples
+	///
+	/// ```
+	/// use embedded_ffi::{OsStr, OsString};
+	///
+	/// let s = OsStr::new("Grüße, Jürgen ❤");
+	/// assert_eq!(s.to_ascii_uppercase(), OsString::from("GRüßE, JüRGEN ❤"));
+	/// ```
+	#[cfg(feature = "alloc")]
+	pub fn to_ascii_uppercase(&self) -> OsString {
+		OsString {
+			inner: Buf {
+				inner: self.bytes().to_ascii_uppercase(),
+			},
+		}
+	}
+
+	/// Converts this string to its ASCII lower case equivalent in-place.
+	///
+	/// ASCII letters 'A' to 'Z' are mapped to 'a' to 'z', but non-ASCII
+	/// letters are unchanged.
+	///
+	/// To return a new lowercased value without modifying the existing one,
+	/// use [`to_ascii_lowercase`].
+	///
+	/// [`to_ascii_lowercase`]: OsStr::to_ascii_lowercase
+	///
+	/// # Examples
+	///
+	/// ```
+	/// use embedded_ffi::ArrayOsString;
+	/// use std::convert::TryFrom;
+	///
+	/// let mut s = ArrayOsString::<16>::try_from("GRÜßE").unwrap();
+	/// s.make_ascii_lowercase();
+	/// assert_eq!(s, "grÜße");
+	/// ```
+	pub fn make_ascii_lowercase(&mut self) {
+		self.bytes_mut().make_ascii_lowercase()
+	}
+
+	/// Converts this string to its ASCII upper case equivalent in-place.
+	///
+	/// ASCII letters 'a' to 'z' are mapped to 'A' to 'Z', but non-ASCII
+	/// letters are unchanged.
+	///
+	/// To return a new uppercased value without modifying the existing one,
+	/// use [`to_ascii_uppercase`].
+	///
+	/// [`to_ascii_uppercase`]: OsStr::to_ascii_uppercase
+	///
+	/// # Examples
+	///
+	/// ```
+	/// use embedded_ffi::ArrayOsString;
+	/// use std::convert::TryFrom;
+	///
+	/// let mut s = ArrayOsString::<16>::try_from("Grüße").unwrap();
+	/// s.make_ascii_uppercase();
+	/// assert_eq!(s, "GRüßE");
+	/// ```
+	pub fn make_ascii_uppercase(&mut self) {
+		self.bytes_mut().make_ascii_uppercase()
+	}
+
+	/// Returns a sub-slice with leading and trailing ASCII whitespace
+	/// removed.
+	///
+	/// Only ASCII whitespace is recognized, since the string is not assumed
+	/// to be valid UTF-8.
+	///
+	/// # Examples
+	///
+	/// ```
+	/// use embedded_ffi::OsStr;
+	///
+	/// let s = OsStr::new("\n Hello\tworld\t\r\n");
+	/// assert_eq!(s.trim(), OsStr::new("Hello\tworld"));
+	/// ```
+	pub fn trim(&self) -> &OsStr {
+		self.trim_start().trim_end()
+	}
+
+	/// Returns a sub-slice with leading ASCII whitespace removed.
+	///
+	/// # Examples
+	///
+	/// ```
+	/// use embedded_ffi::OsStr;
+	///
+	/// let s = OsStr::new("\n Hello\tworld\t\r\n");
+	/// assert_eq!(s.trim_start(), OsStr::new("Hello\tworld\t\r\n"));
+	/// ```
+	pub fn trim_start(&self) -> &OsStr {
+		let bytes = self.bytes();
+		let start = bytes
+			.iter()
+			.position(|b| !b.is_ascii_whitespace())
+			.unwrap_or(bytes.len());
+		OsStr::from_inner(Slice::from_u8_slice(&bytes[start..]))
+	}
+
+	/// Returns a sub-slice with trailing ASCII whitespace removed.
+	///
+	/// # Examples
+	///
+	/// ```
+	/// use embedded_ffi::OsStr;
+	///
+	/// let s = OsStr::new("\n Hello\tworld\t\r\n");
+	/// assert_eq!(s.trim_end(), OsStr::new("\n Hello\tworld"));
+	/// ```
+	pub fn trim_end(&self) -> &OsStr {
+		let bytes = self.bytes();
+		let end = bytes
+			.iter()
+			.rposition(|b| !b.is_ascii_whitespace())
+			.map_or(0, |i| i + 1);
+		OsStr::from_inner(Slice::from_u8_slice(&bytes[..end]))
+	}
+
+	/// Returns a sub-slice with all prefixes and suffixes that match a
+	/// pattern repeatedly removed.
+	///
+	/// # Examples
+	///
+	/// ```
+	/// use embedded_ffi::OsStr;
+	///
+	/// assert_eq!(OsStr::new("11foo1bar11").trim_matches('1'), OsStr::new("foo1bar"));
+	/// assert_eq!(OsStr::new("123foo1bar123").trim_matches(|b: u8| b.is_ascii_digit()), OsStr::new("foo1bar"));
+	/// assert_eq!(OsStr::new("xyxfooxy").trim_matches("xy"), OsStr::new("xfoo"));
+	/// ```
+	pub fn trim_matches<P: Pattern>(&self, mut pat: P) -> &OsStr {
+		let mut bytes = self.bytes();
+		while let Some(len @ 1..) = pat.prefix_len(bytes) {
+			bytes = &bytes[len..];
+		}
+		while let Some(len @ 1..) = pat.suffix_len(bytes) {
+			bytes = &bytes[..bytes.len() - len];
+		}
+		OsStr::from_inner(Slice::from_u8_slice(bytes))
+	}
+
+	/// Returns a sub-slice of this `OsStr`, or [`None`] if the index is out
+	/// of bounds.
+	///
+	/// The index is a byte offset or range of byte offsets. Since an `OsStr`
+	/// is an arbitrary sequence of bytes, every offset within the string is a
+	/// valid boundary.
+	///
+	/// # Examples
+	///
+	/// ```
+	/// use embedded_ffi::{OsStr, OsStrExt};
+	///
+	/// let s = OsStr::from_bytes(b"ab\xFFcd");
+	/// assert_eq!(s.get(1..3), Some(OsStr::from_bytes(b"b\xFF")));
+	/// assert_eq!(s.get(3..), Some(OsStr::new("cd")));
+	/// assert_eq!(s.get(4..6), None);
+	/// ```
+	pub fn get<I: SliceIndex<[u8], Output = [u8]>>(&self, index: I) -> Option<&OsStr> {
+		self.bytes()
+			.get(index)
+			.map(|bytes| OsStr::from_inner(Slice::from_u8_slice(bytes)))
+	}
+
+	/// Returns a mutable sub-slice of this `OsStr`, or [`None`] if the index
+	/// is out of bounds.
+	///
+	/// # Examples
+	///
+	/// ```
+	/// use embedded_ffi::{OsStr, OsStrExt};
+	///
+	/// let mut bytes = *b"abcd";
+	/// let s = OsStr::from_bytes_mut(&mut bytes);
+	/// s.get_mut(..2).unwrap().make_ascii_uppercase();
+	/// assert_eq!(s, "ABcd");
+	/// assert!(s.get_mut(..5).is_none());
+	/// ```
+	pub fn get_mut<I: SliceIndex<[u8], Output = [u8]>>(&mut self, index: I) -> Option<&mut OsStr> {
+		self.bytes_mut()
+			.get_mut(index)
+			.map(|bytes| OsStr::from_inner_mut(Slice::from_u8_slice_mut(bytes)))
+	}
+
+	/// Divides this `OsStr` into two at a byte offset.
+	///
+	/// The first slice contains the bytes `[0, mid)` and the second the
+	/// bytes `[mid, len)`.
+	///
+	/// # Panics
+	///
+	/// Panics if `mid > len`.
+	///
+	/// # Examples
+	///
+	/// ```
+	/// use embedded_ffi::OsStr;
+	///
+	/// let (first, last) = OsStr::new("Per Martin-Löf").split_at(3);
+	/// assert_eq!(first, "Per");
+	/// assert_eq!(last, " Martin-Löf");
+	/// ```
+	pub fn split_at(&self, mid: usize) -> (&OsStr, &OsStr) {
+		let (first, last) = self.bytes().split_at(mid);
+		(
+			OsStr::from_inner(Slice::from_u8_slice(first)),
+			OsStr::from_inner(Slice::from_u8_slice(last)),
+		)
+	}
+
+	/// Replaces all matches of a pattern with another string.
+	///
+	/// `replace` creates a new [`OsString`] and copies the data from this
+	/// `OsStr` into it. While doing so, it attempts to find matches of the
+	/// pattern. If it finds any, it replaces them with the replacement
+	/// string. The bytes of both strings are copied unchanged, so non-UTF-8
+	/// data is preserved.
+	///
+	/// # Examples
+	///
+	/// ```
+	/// use embedded_ffi::{OsStr, OsStrExt};
+	///
+	/// let path = OsStr::from_bytes(b"/mnt/sd/\xFF.log");
+	/// assert_eq!(path.replace("/mnt/sd", "/data").as_bytes(), b"/data/\xFF.log");
+	/// ```
+	#[cfg(feature = "alloc")]
+	pub fn replace<P: Pattern, T: AsRef<OsStr>>(&self, from: P, to: T) -> OsString {
+		let mut result = OsString::with_capacity(self.len());
+		for (i, piece) in self.split(from).enumerate() {
+			if i > 0 {
+				result.push(&to);
+			}
+			result.push(piece);
+		}
+		result
+	}
+
+	/// Replaces the first `count` matches of a pattern with another string.
+	///
+	/// # Examples
+	///
+	/// ```
+	/// use embedded_ffi::OsStr;
+	///
+	/// let s = OsStr::new("foo foo 123 foo");
+	/// assert_eq!(s.replacen("foo", "new", 2), "new new 123 foo");
+	/// assert_eq!(s.replacen(' ', "", 10), "foofoo123foo");
+	/// ```
+	#[cfg(feature = "alloc")]
+	pub fn replacen<P: Pattern, T: AsRef<OsStr>>(&self, from: P, to: T, count: usize) -> OsString {
+		let mut result = OsString::with_capacity(self.len());
+		for (i, piece) in self.splitn(count.saturating_add(1), from).enumerate() {
+			if i > 0 {
+				result.push(&to);
+			}
+			result.push(piece);
+		}
+		result
+	}
+
+	/// Replaces all matches of a pattern with another string, writing the
+	/// result into `buf`.
+	///
+	/// This is the allocation-free counterpart to [`replace`]. On success,
+	/// returns the number of bytes written to the start of `buf`.
+	///
+	/// [`replace`]: OsStr::replace
+	///
+	/// # Errors
+	///
+	/// Returns [`CapacityError`] if the result does not fit into `buf`. The
+	/// contents of `buf` are unspecified in that case.
+	///
+	/// # Examples
+	///
+	/// ```
+	/// use embedded_ffi::{OsStr, OsStrExt};
+	///
+	/// let mut buf = [0; 16];
+	/// let len = OsStr::new("/mnt/sd/log").replace_into("/mnt/sd", "/data", &mut buf).unwrap();
+	/// assert_eq!(OsStr::from_bytes(&buf[..len]), "/data/log");
+	///
+	/// assert!(OsStr::new("aaaa").replace_into('a', "xxxxx", &mut buf).is_err());
+	/// ```
+	pub fn replace_into<P: Pattern, T: AsRef<OsStr>>(
+		&self,
+		from: P,
+		to: T,
+		buf: &mut [u8],
+	) -> Result<usize, CapacityError> {
+		let mut len = 0;
+		let mut push = |bytes: &[u8]| {
+			let dst = buf
+				.get_mut(len..len + bytes.len())
+				.ok_or(CapacityError(()))?;
+			dst.copy_from_slice(bytes);
+			len += bytes.len();
+			Ok(())
+		};
+		for (i, piece) in self.split(from).enumerate() {
+			if i > 0 {
+				push(to.as_ref().bytes())?;
+			}
+			push(piece.bytes())?;
+		}
+		Ok(len)
+	}
+
+	/// Converts a [`Box`]`<OsStr>` into an [`OsString`] without copying or allocating.
+	#[cfg(feature = "alloc")]
+	pub fn into_os_string(self: Box<OsStr>) -> OsString {
+		let boxed = unsafe { Box::from_raw(Box::into_raw(self) as *mut Slice) };
+		OsString {
+			inner: Buf::from_box(boxed),
+		}
+	}
+
+	/// Gets the underlying byte representation.
+	///
+	/// Note: it is *crucial* that this API is private, to avoid
+	/// revealing the internal, platform-specific encodings.
+	fn bytes(&self) -> &[u8] {
+		unsafe { &*(&self.inner as *const _ as *const [u8]) }
+	}
+
+	fn bytes_mut(&mut self) -> &mut [u8] {
+		unsafe { &mut *(&mut self.inner as *mut _ as *mut [u8]) }
+	}
+
+	/// Returns an object that implements [`Display`] for writing this
+	/// `OsStr` as an escaped string literal.
+	///
+	/// The [`EscapeMode`] selects the target syntax, such as Rust, C, JSON
+	/// or a shell word. Non-UTF-8 data is escaped in a way that is
+	/// appropriate for the mode.
+	///
+	/// [`Display`]: fmt::Display
+	///
+	/// # Examples
+	///
+	/// ```
+	/// use embedded_ffi::{EscapeMode, OsStr, OsStrExt};
+	///
+	/// let name = OsStr::from_bytes(b"it's\xFF");
+	/// assert_eq!(name.escape(EscapeMode::Debug).to_string(), r#""it's\xFF""#);
+	/// assert_eq!(name.escape(EscapeMode::C).to_string(), r#""it's\377""#);
+	/// assert_eq!(name.escape(EscapeMode::Json).to_string(), r#""it's\ufffd""#);
+	/// assert_eq!(name.escape(EscapeMode::Shell).to_string(), r#"'it'\''s'$'\xFF'"#);
+	/// ```
+	pub fn escape(&self, mode: EscapeMode) -> Escape<'_> {
+		Escape::new(self.bytes(), mode)
+	}
+
+	/// Returns an iterator over the valid UTF-8 sequences and invalid bytes
+	/// of this `OsStr`.
+	///
+	/// Each [`Utf8Chunk`] is a valid `&str` followed by the invalid bytes
+	/// that a lossy conversion would replace with a single
+	/// [`U+FFFD REPLACEMENT CHARACTER`][U+FFFD]. This allows custom escaping
+	/// or rendering of non-Unicode data without allocating. The iterator can
+	/// also be consumed from the back.
+	///
+	/// [`Utf8Chunk`]: crate::Utf8Chunk
+	/// [U+FFFD]: core::char::REPLACEMENT_CHARACTER
+	///
+	/// # Examples
+	///
+	/// ```
+	/// use embedded_ffi::{OsStr, OsStrExt};
+	///
+	/// let s = OsStr::from_bytes(b"ab\xFFcd\xE2\x82");
+	/// let mut chunks = s.utf8_chunks();
+	///
+	/// let chunk = chunks.next().unwrap();
+	/// assert_eq!((chunk.valid(), chunk.invalid()), ("ab", &b"\xFF"[..]));
+	///
+	/// let chunk = chunks.next_back().unwrap();
+	/// assert_eq!((chunk.valid(), chunk.invalid()), ("cd", &b"\xE2\x82"[..]));
+	/// assert!(chunks.next().is_none());
+	/// ```
+	pub fn utf8_chunks(&self) -> Utf8Chunks<'_> {
+		Utf8Chunks::new(self.bytes())
+	}
+
+	/// Returns an iterator over the UTF-16 code units of this `OsStr`,
+	/// decoding it as [WTF-8].
+	///
+	/// This is the inverse of [`WStr::to_os_string_wtf8`]: unpaired
+	/// surrogates that are encoded as three-byte sequences are returned as
+	/// they are. The iterator returns an error and stops at the first
+	/// sequence that is not well-formed WTF-8.
+	///
+	/// [WTF-8]: https://simonsapin.github.io/wtf-8/
+	/// [`WStr::to_os_string_wtf8`]: crate::WStr::to_os_string_wtf8
+	///
+	/// # Examples
+	///
+	/// ```
+	/// use embedded_ffi::{OsStr, OsStrExt};
+	///
+	/// let name = OsStr::from_bytes(b"a\xED\xA0\x80");
+	/// let mut units = name.decode_wtf8();
+	/// assert_eq!(units.next(), Some(Ok(0x61)));
+	/// assert_eq!(units.next(), Some(Ok(0xD800)));
+	/// assert_eq!(units.next(), None);
+	/// ```
+	pub fn decode_wtf8(&self) -> DecodeWtf8<'_> {
+		DecodeWtf8::new(self.bytes())
+	}
+
+	/// Returns an object that implements [`Display`] for safely printing an
+	/// `OsStr` that may contain non-Unicode data.
+	///
+	/// Any non-Unicode sequences are replaced with
+	/// [`U+FFFD REPLACEMENT CHARACTER`][U+FFFD] while formatting, without
+	/// allocating. Width, fill, alignment and precision flags are applied to
+	/// the resulting characters, as they would be for a `str`.
+	///
+	/// [`Display`]: fmt::Display
+	/// [U+FFFD]: core::char::REPLACEMENT_CHARACTER
+	///
+	/// # Examples
+	///
+	/// ```
+	/// use embedded_ffi::{OsStr, OsStrExt};
+	///
+	/// let name = OsStr::from_bytes(b"fo\xFFo");
+	/// assert_eq!(format!("{}", name.display()), "fo\u{FFFD}o");
+	/// assert_eq!(format!("[{:>6.3}]", name.display()), "[   fo\u{FFFD}]");
+	/// ```
+	pub fn display(&self) -> Display<'_> {
+		Display { os_str: self }
+	}
+}
+
+/// Helper struct for safely printing an [`OsStr`] with [`format!`] and `{}`.
+///
+/// An [`OsStr`] might contain non-Unicode data. This `struct` implements the
+/// [`Display`] trait in a way that mitigates that: invalid sequences are
+/// replaced with [`U+FFFD REPLACEMENT CHARACTER`][U+FFFD]. It is created by
+/// the [`display`] methods on [`OsStr`] and [`Path`].
+///
+/// [`Display`]: fmt::Display
+/// [`format!`]: alloc::format
+/// [U+FFFD]: core::char::REPLACEMENT_CHARACTER
+/// [`display`]: OsStr::display
+/// [`Path`]: crate::Path
+pub struct Display<'a> {
+	os_str: &'a OsStr,
+}
+
+impl fmt::Debug for Display<'_> {
+	fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+		fmt::Debug::fmt(self.os_str, formatter)
+	}
+}
+
+impl fmt::Display for Display<'_> {
+	fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+		fmt::Display::fmt(&self.os_str.inner, formatter)
+	}
+}
+
+#[cfg(feature = "alloc")]
+impl From<&OsStr> for Box<OsStr> {
+	fn from(s: &OsStr) -> Box<OsStr> {
+		let rw = Box::into_raw(s.inner.into_box()) as *mut OsStr;
+		unsafe { Box::from_raw(rw) }
+	}
+}
+
+#[cfg(feature = "alloc")]
 impl From<Box<OsStr>> for OsString {
 	/// Converts a `Box<OsStr>` into a `OsString` without copying or allocating.
 	fn from(boxed: Box<OsStr>) -> OsString {
@@ -765,6 +2009,17 @@ impl Default for Box<OsStr> {
 	}
 }
 
+impl<'a> From<&'a CStr> for &'a OsStr {
+	/// Converts a [`CStr`] into an [`OsStr`] without copying, dropping the
+	/// nul terminator.
+	///
+	/// [`CStr`]: crate::CStr
+	#[inline]
+	fn from(s: &'a CStr) -> &'a OsStr {
+		OsStr::from_inner(Slice::from_u8_slice(s.to_bytes()))
+	}
+}
+
 impl Default for &OsStr {
 	/// Creates an empty `OsStr`.
 	#[inline]
@@ -773,6 +2028,22 @@ impl Default for &OsStr {
 	}
 }
 
+impl<I: SliceIndex<[u8], Output = [u8]>> ops::Index<I> for OsStr {
+	type Output = OsStr;
+
+	#[inline]
+	fn index(&self, index: I) -> &OsStr {
+		OsStr::from_inner(Slice::from_u8_slice(&self.bytes()[index]))
+	}
+}
+
+impl<I: SliceIndex<[u8], Output = [u8]>> ops::IndexMut<I> for OsStr {
+	#[inline]
+	fn index_mut(&mut self, index: I) -> &mut OsStr {
+		OsStr::from_inner_mut(Slice::from_u8_slice_mut(&mut self.bytes_mut()[index]))
+	}
+}
+
 impl PartialEq for OsStr {
 	fn eq(&self, other: &OsStr) -> bool {
 		self.bytes().eq(other.bytes())
@@ -949,6 +2220,14 @@ impl IntoInner<Buf> for OsString {
 	}
 }
 
+#[cfg(feature = "alloc")]
+impl AsInnerMut<Buf> for OsString {
+	#[inline]
+	fn as_inner_mut(&mut self) -> &mut Buf {
+		&mut self.inner
+	}
+}
+
 impl AsInner<Slice> for OsStr {
 	#[inline]
 	fn as_inner(&self) -> &Slice {
@@ -956,11 +2235,19 @@ impl AsInner<Slice> for OsStr {
 	}
 }
 
+impl AsInnerMut<Slice> for OsStr {
+	#[inline]
+	fn as_inner_mut(&mut self) -> &mut Slice {
+		&mut self.inner
+	}
+}
+
 #[cfg(feature = "alloc")]
 #[cfg(test)]
 mod tests {
 	use super::*;
 	use crate::sys_common::{AsInner, IntoInner};
+	use crate::{OsStrExt, OsStringExt};
 
 	use alloc::rc::Rc;
 	use alloc::sync::Arc;
@@ -1051,6 +2338,16 @@ mod tests {
 		assert_eq!("", &os_string);
 	}
 
+	#[test]
+	fn test_os_string_cmp() {
+		let a = OsString::from("abc");
+		let b = OsString::from("abd");
+		assert_eq!(a, a.clone());
+		assert_ne!(a, b);
+		assert!(a < b && a <= b && b > a && b >= a);
+		assert_eq!(a.cmp(&b), cmp::Ordering::Less);
+	}
+
 	#[test]
 	fn test_os_str_is_empty() {
 		let mut os_string = OsString::new();
@@ -1075,12 +2372,171 @@ mod tests {
 		assert_eq!(0, os_string.len());
 	}
 
+	#[test]
+	fn test_os_str_const() {
+		const OS_STR: &OsStr = OsStr::from_str_const("abc");
+		const LEN: usize = OS_STR.len();
+		const EMPTY: bool = OsStr::from_bytes_const(b"").is_empty();
+		const UTF8: Option<&str> = OS_STR.to_str();
+		const NON_UTF8: Option<&str> = OsStr::from_bytes_const(b"\xFF").to_str();
+		assert_eq!(LEN, 3);
+		assert!(EMPTY);
+		assert_eq!(UTF8, Some("abc"));
+		assert_eq!(NON_UTF8, None);
+	}
+
+	#[test]
+	fn test_os_str_ascii_case() {
+		let name = OsStr::from_bytes_const(b"Read\xFFMe.TXT");
+		assert!(name.eq_ignore_ascii_case(OsStr::from_bytes_const(b"READ\xFFME.txt")));
+		assert!(!name.eq_ignore_ascii_case(OsStr::from_bytes_const(b"READ\xFEME.txt")));
+		assert_eq!(
+			name.to_ascii_lowercase(),
+			OsStr::from_bytes_const(b"read\xFFme.txt")
+		);
+		assert_eq!(
+			name.to_ascii_uppercase(),
+			OsStr::from_bytes_const(b"READ\xFFME.TXT")
+		);
+
+		let mut boxed = OsString::from("MiXeD").into_boxed_os_str();
+		boxed.make_ascii_lowercase();
+		assert_eq!(boxed.to_str(), Some("mixed"));
+	}
+
+	#[test]
+	fn test_os_string_mut_bytes() {
+		let mut os_string = OsString::from("abc");
+		os_string.as_bytes_mut()[1] = 0xFF;
+		assert_eq!(os_string.as_bytes(), b"a\xFFc");
+		os_string.as_mut_vec().push(b'd');
+		assert_eq!(os_string.len(), 4);
+		assert_eq!(os_string.into_vec(), b"a\xFFcd");
+	}
+
+	#[test]
+	fn test_os_str_index() {
+		let s = OsStr::from_bytes(b"ab\xFFcd");
+		assert_eq!(&s[..2], "ab");
+		assert_eq!(s[2..3].as_bytes(), b"\xFF");
+		assert_eq!(&s[3..=4], "cd");
+		assert_eq!(s.get(5..), Some(OsStr::new("")));
+		assert_eq!(s.get(6..), None);
+		assert_eq!(s.split_at(0), (OsStr::new(""), s));
+
+		let mut os_string = OsString::from("abcd");
+		os_string[1..3].make_ascii_uppercase();
+		assert_eq!(os_string, "aBCd");
+		assert_eq!(&os_string[1..], "BCd");
+	}
+
+	#[test]
+	fn test_os_string_edit() {
+		let mut s = OsString::from("abcdef");
+		s.truncate(10);
+		assert_eq!(s, "abcdef");
+		s.insert_os_str(6, OsStr::from_bytes(b"\xFF"));
+		assert_eq!(s.pop(), Some(0xFF));
+		s.remove(1..3);
+		assert_eq!(s, "adef");
+		s.replace_range(1.., "xyz");
+		assert_eq!(s, "axyz");
+		{
+			let mut drain = s.drain(1..3);
+			assert_eq!(drain.next_back(), Some(b'y'));
+			assert_eq!(drain.len(), 1);
+		}
+		assert_eq!(s, "az");
+		s.retain(|b| b == b'a');
+		assert_eq!(s, "a");
+		assert_eq!(s.split_off(0), "a");
+		assert!(s.is_empty());
+	}
+
+	#[test]
+	#[should_panic]
+	fn test_os_string_insert_out_of_bounds() {
+		OsString::from("ab").insert_os_str(3, "c");
+	}
+
+	#[test]
+	fn test_os_str_replace() {
+		let s = OsStr::from_bytes(b"a\xFFb\xFFc");
+		assert_eq!(s.replace(0xFF, "--"), "a--b--c");
+		assert_eq!(s.replacen(0xFF, "", 1).as_bytes(), b"ab\xFFc");
+		assert_eq!(s.replacen(0xFF, "", 0).as_bytes(), s.as_bytes());
+		assert_eq!(OsStr::new("ab").replace("", "x"), "xaxbx");
+		assert_eq!(OsStr::new("").replace("", "x"), "x");
+
+		let mut buf = [0; 5];
+		assert_eq!(s.replace_into(0xFF, "", &mut buf), Ok(3));
+		assert_eq!(&buf[..3], b"abc");
+		assert_eq!(
+			s.replace_into(0xFF, "", &mut buf[..2]),
+			Err(CapacityError(()))
+		);
+		assert_eq!(s.replace_into(0xFF, "-", &mut buf), Ok(5));
+		assert_eq!(&buf, b"a-b-c");
+	}
+
+	#[test]
+	fn test_os_str_display() {
+		use alloc::format;
+
+		let s = OsStr::from_bytes(b"a\xFFb");
+		assert_eq!(format!("{}", s.display()), "a\u{FFFD}b");
+		assert_eq!(format!("{:5}|", s.display()), "a\u{FFFD}b  |");
+		assert_eq!(format!("{:*^6}", s.display()), "*a\u{FFFD}b**");
+		assert_eq!(format!("{:.1}", s.display()), "a");
+		assert_eq!(format!("{:.2}", s.display()), "a\u{FFFD}");
+		assert_eq!(format!("{:>4.0}", s.display()), "    ");
+		assert_eq!(format!("{:>4}", OsStr::new("ab").display()), "  ab");
+		assert_eq!(format!("{:?}", s.display()), format!("{:?}", s));
+	}
+
+	#[test]
+	fn test_os_string_write() {
+		use core::fmt::Write;
+
+		let mut s = OsString::from(OsStr::from_bytes(b"\xFF"));
+		let (name, n) = ("a", 7);
+		write!(s, "{}-{:02}", name, n).unwrap();
+		assert_eq!(s.as_bytes(), b"\xFFa-07");
+	}
+
+	#[test]
+	fn test_os_str_trim() {
+		let s = OsStr::from_bytes_const(b" \t\xFF x\xFF\r\n");
+		assert_eq!(s.trim(), OsStr::from_bytes_const(b"\xFF x\xFF"));
+		assert_eq!(OsStr::new(" \n ").trim(), "");
+		assert_eq!(OsStr::new("").trim_start(), "");
+		assert_eq!(OsStr::new("aaa").trim_matches('a'), "");
+		assert_eq!(OsStr::new("abc").trim_matches(""), "abc");
+	}
+
 	#[test]
 	fn test_os_str_default() {
 		let os_str: &OsStr = Default::default();
 		assert_eq!("", os_str);
 	}
 
+	#[test]
+	fn test_os_str_cstr_roundtrip() {
+		let c_str = CStr::from_bytes_with_nul(b"uart0\0").unwrap();
+		let os_str: &OsStr = c_str.into();
+		assert_eq!(os_str, "uart0");
+		assert_eq!(os_str.to_cstring().unwrap().as_c_str(), c_str);
+
+		let with_nul = OsStr::new("uart0\0");
+		assert_eq!(with_nul.to_cstr(), Ok(c_str));
+		assert_eq!(
+			with_nul.to_cstr().unwrap().as_ptr() as *const u8,
+			with_nul.bytes().as_ptr()
+		);
+		assert!(OsStr::new("uart0\0\0").to_cstr().is_err());
+		assert_eq!(with_nul.to_cstring().unwrap_err().nul_position(), 5);
+	}
+
 	#[test]
 	fn into_boxed() {
 		let orig = "Hello, world!";
@@ -0,0 +1,1892 @@
+//! Cross-platform path manipulation, following Unix conventions.
+//!
+//! This module provides two types, [`PathBuf`] and [`Path`] (akin to
+//! [`OsString`] and [`OsStr`]), for working with paths abstractly. Paths are
+//! always interpreted with Unix semantics: the only separator is `/`, and a
+//! path is absolute if and only if it starts with `/`.
+//!
+//! [`PathBuf`]: crate::PathBuf
+//! [`OsString`]: crate::OsString
+#[cfg(feature = "alloc")]
+use alloc::borrow::{Cow, ToOwned};
+#[cfg(feature = "alloc")]
+use alloc::boxed::Box;
+#[cfg(feature = "alloc")]
+use alloc::string::String;
+#[cfg(feature = "alloc")]
+use alloc::vec::Vec;
+#[cfg(feature = "alloc")]
+use core::borrow::Borrow;
+use core::cmp;
+use core::fmt;
+use core::hash::{Hash, Hasher};
+use core::iter::FusedIterator;
+#[cfg(feature = "alloc")]
+use core::ops;
+
+use crate::CapacityError;
+use crate::os_str::Display;
+use crate::OsStr;
+use crate::OsStrExt;
+#[cfg(feature = "alloc")]
+use crate::{OsString, OsStringExt};
+
+/// The separator of path components, `/`.
+pub const MAIN_SEPARATOR: char = '/';
+
+const SEP: u8 = b'/';
+
+/// Iterates through `iter` while it matches `prefix`; returns `None` if
+/// `prefix` is not a prefix of `iter`, otherwise `Some(iter_after_prefix)`.
+fn iter_after<'a, 'b, I, J>(mut iter: I, mut prefix: J) -> Option<I>
+where
+	I: Iterator<Item = Component<'a>> + Clone,
+	J: Iterator<Item = Component<'b>>,
+{
+	loop {
+		let mut iter_next = iter.clone();
+		match (iter_next.next(), prefix.next()) {
+			(Some(ref x), Some(ref y)) if x == y => (),
+			(Some(_), Some(_)) => return None,
+			(Some(_), None) => return Some(iter),
+			(None, None) => return Some(iter),
+			(None, Some(_)) => return None,
+		}
+		iter = iter_next;
+	}
+}
+
+/// Says whether the first byte after the root is a separator.
+fn has_physical_root(path: &[u8]) -> bool {
+	!path.is_empty() && path[0] == SEP
+}
+
+/// Storage that a path can be lexically normalized into.
+trait NormalizeSink {
+	fn bytes(&self) -> &[u8];
+	fn push_slice(&mut self, s: &[u8]) -> Result<(), CapacityError>;
+	fn truncate(&mut self, len: usize);
+}
+
+#[cfg(feature = "alloc")]
+impl NormalizeSink for Vec<u8> {
+	fn bytes(&self) -> &[u8] {
+		self
+	}
+
+	fn push_slice(&mut self, s: &[u8]) -> Result<(), CapacityError> {
+		self.extend_from_slice(s);
+		Ok(())
+	}
+
+	fn truncate(&mut self, len: usize) {
+		Vec::truncate(self, len)
+	}
+}
+
+/// A caller-provided buffer and the length of its initialized part.
+struct SliceSink<'a> {
+	buf: &'a mut [u8],
+	len: usize,
+}
+
+impl NormalizeSink for SliceSink<'_> {
+	fn bytes(&self) -> &[u8] {
+		&self.buf[..self.len]
+	}
+
+	fn push_slice(&mut self, s: &[u8]) -> Result<(), CapacityError> {
+		let end = self.len + s.len();
+		if end > self.buf.len() {
+			return Err(CapacityError(()));
+		}
+		self.buf[self.len..end].copy_from_slice(s);
+		self.len = end;
+		Ok(())
+	}
+
+	fn truncate(&mut self, len: usize) {
+		self.len = cmp::min(self.len, len);
+	}
+}
+
+/// Writes the lexically normalized form of `path` into `out`.
+///
+/// `out` must be empty when this is called.
+fn normalize_into<S: NormalizeSink>(path: &Path, out: &mut S) -> Result<(), CapacityError> {
+	// Everything before `floor` is a root or a run of leading `..`s, which
+	// cannot be removed by a following `..`.
+	let mut floor = 0;
+	for component in path.components() {
+		match component {
+			Component::RootDir => {
+				out.push_slice(b"/")?;
+				floor = 1;
+			}
+			Component::CurDir => {}
+			Component::ParentDir if out.bytes().len() > floor => {
+				let bytes = out.bytes();
+				let len = bytes[floor..]
+					.iter()
+					.rposition(|&b| b == SEP)
+					.map_or(floor, |i| floor + i);
+				out.truncate(len);
+			}
+			Component::ParentDir if path.has_root() => {}
+			Component::ParentDir | Component::Normal(_) => {
+				if matches!(out.bytes().last(), Some(&b) if b != SEP) {
+					out.push_slice(b"/")?;
+				}
+				out.push_slice(component.as_os_str().as_bytes())?;
+				if component == Component::ParentDir {
+					floor = out.bytes().len();
+				}
+			}
+		}
+	}
+	if out.bytes().is_empty() && !path.as_os_str().is_empty() {
+		out.push_slice(b".")?;
+	}
+	Ok(())
+}
+
+/// Whether [`normalize_into`] would reproduce `path` unchanged.
+#[cfg(feature = "alloc")]
+fn is_normalized(path: &[u8]) -> bool {
+	if path == b"." {
+		return true;
+	}
+	let (rooted, body) = match path.split_first() {
+		Some((&SEP, body)) => (true, body),
+		_ => (false, path),
+	};
+	if body.is_empty() {
+		return true;
+	}
+	let mut only_parents = !rooted;
+	body.split(|&b| b == SEP).all(|segment| match segment {
+		b"" | b"." => false,
+		b".." => only_parents,
+		_ => {
+			only_parents = false;
+			true
+		}
+	})
+}
+
+/// Splits a file name into the part before and after its final `.`.
+///
+/// A leading `.` does not start an extension, and `..` has neither.
+fn rsplit_file_at_dot(file: &[u8]) -> (Option<&[u8]>, Option<&[u8]>) {
+	if file == b".." {
+		return (Some(file), None);
+	}
+
+	match file.iter().rposition(|&b| b == b'.') {
+		Some(0) | None => (Some(file), None),
+		Some(i) => (Some(&file[..i]), Some(&file[i + 1..])),
+	}
+}
+
+/// An error returned from [`Path::strip_prefix`] if the prefix was not found.
+///
+/// This `struct` is created by the [`strip_prefix`] method on [`Path`].
+/// See its documentation for more.
+///
+/// [`strip_prefix`]: Path::strip_prefix
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StripPrefixError(());
+
+impl fmt::Display for StripPrefixError {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		f.write_str("prefix not found")
+	}
+}
+
+/// A single component of a path.
+///
+/// A `Component` roughly corresponds to a substring between path separators
+/// (`/`).
+///
+/// This `enum` is created by iterating over [`Components`], which in turn is
+/// created by the [`components`](Path::components) method on [`Path`].
+///
+/// # Examples
+///
+/// ```
+/// use embedded_ffi::path::{Component, Path};
+///
+/// let path = Path::new("/tmp/foo/bar.txt");
+/// let components = path.components().collect::<Vec<_>>();
+/// assert_eq!(&components, &[
+///     Component::RootDir,
+///     Component::Normal("tmp".as_ref()),
+///     Component::Normal("foo".as_ref()),
+///     Component::Normal("bar.txt".as_ref()),
+/// ]);
+/// ```
+#[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Debug)]
+pub enum Component<'a> {
+	/// The root directory component, appears after any prefix and before
+	/// anything else.
+	///
+	/// It represents a separator that designates that a path starts from
+	/// root.
+	RootDir,
+
+	/// A reference to the current directory, i.e., `.`.
+	CurDir,
+
+	/// A reference to the parent directory, i.e., `..`.
+	ParentDir,
+
+	/// A normal component, e.g., `a` and `b` in `a/b`.
+	///
+	/// This variant is the most common one, it represents references to files
+	/// or directories.
+	Normal(&'a OsStr),
+}
+
+impl<'a> Component<'a> {
+	/// Extracts the underlying [`OsStr`] slice.
+	///
+	/// # Examples
+	///
+	/// ```
+	/// use embedded_ffi::Path;
+	///
+	/// let path = Path::new("./tmp/foo/bar.txt");
+	/// let components: Vec<_> = path.components().map(|comp| comp.as_os_str()).collect();
+	/// assert_eq!(&components, &[".", "tmp", "foo", "bar.txt"]);
+	/// ```
+	pub fn as_os_str(self) -> &'a OsStr {
+		match self {
+			Component::RootDir => OsStr::new("/"),
+			Component::CurDir => OsStr::new("."),
+			Component::ParentDir => OsStr::new(".."),
+			Component::Normal(path) => path,
+		}
+	}
+}
+
+impl AsRef<OsStr> for Component<'_> {
+	fn as_ref(&self) -> &OsStr {
+		self.as_os_str()
+	}
+}
+
+impl AsRef<Path> for Component<'_> {
+	fn as_ref(&self) -> &Path {
+		self.as_os_str().as_ref()
+	}
+}
+
+/// Component parsing works by a double-ended state machine; the cursors at the
+/// front and back of the path each keep track of what parts of the path have
+/// been consumed so far.
+///
+/// Going front to back, a path is made up of a root directory, an optional
+/// leading `.` and then a body of normal components.
+#[derive(Copy, Clone, PartialEq, PartialOrd, Debug)]
+enum State {
+	StartDir = 0, // / or . or nothing
+	Body = 1,     // foo/bar/baz
+	Done = 2,
+}
+
+/// An iterator over the [`Component`]s of a [`Path`].
+///
+/// This `struct` is created by the [`components`] method on [`Path`].
+/// See its documentation for more.
+///
+/// Repeated separators are ignored, so `a/b` and `a//b` both have `a` and `b`
+/// as components. Occurrences of `.` are normalized away, except if they are
+/// at the beginning of the path, so `a/./b`, `a/b/`, `a/b/.` and `a/b` all
+/// have `a` and `b` as components, but `./a/b` starts with an additional
+/// [`CurDir`] component. Occurrences of `..` are left as is.
+///
+/// # Examples
+///
+/// ```
+/// use embedded_ffi::Path;
+///
+/// let path = Path::new("/tmp/foo/bar.txt");
+///
+/// for component in path.components() {
+///     println!("{:?}", component);
+/// }
+/// ```
+///
+/// [`components`]: Path::components
+/// [`CurDir`]: Component::CurDir
+#[derive(Clone)]
+pub struct Components<'a> {
+	// The path left to parse components from
+	path: &'a [u8],
+
+	// true if path *physically* has a root separator
+	has_physical_root: bool,
+
+	// The iterator is double-ended, and these two states keep track of what has
+	// been produced from either end
+	front: State,
+	back: State,
+}
+
+impl<'a> Components<'a> {
+	// how long is the prefix, if any, before the body?
+	#[inline]
+	fn len_before_body(&self) -> usize {
+		let root = if self.front <= State::StartDir && self.has_physical_root {
+			1
+		} else {
+			0
+		};
+		let cur_dir = if self.front <= State::StartDir && self.include_cur_dir() {
+			1
+		} else {
+			0
+		};
+		root + cur_dir
+	}
+
+	// is the iteration complete?
+	#[inline]
+	fn finished(&self) -> bool {
+		self.front == State::Done || self.back == State::Done || self.front > self.back
+	}
+
+	/// Extracts a slice corresponding to the portion of the path remaining for
+	/// iteration.
+	///
+	/// # Examples
+	///
+	/// ```
+	/// use embedded_ffi::Path;
+	///
+	/// let mut components = Path::new("/tmp/foo/bar.txt").components();
+	/// components.next();
+	/// components.next();
+	///
+	/// assert_eq!(Path::new("foo/bar.txt"), components.as_path());
+	/// ```
+	pub fn as_path(&self) -> &'a Path {
+		let mut comps = self.clone();
+		if comps.front == State::Body {
+			comps.trim_left();
+		}
+		if comps.back == State::Body {
+			comps.trim_right();
+		}
+		Path::from_bytes(comps.path)
+	}
+
+	/// Is the *original* path rooted?
+	fn has_root(&self) -> bool {
+		self.has_physical_root
+	}
+
+	/// Should the normalized path include a leading `.`?
+	fn include_cur_dir(&self) -> bool {
+		if self.has_root() {
+			return false;
+		}
+		let mut iter = self.path.iter();
+		match (iter.next(), iter.next()) {
+			(Some(&b'.'), None) => true,
+			(Some(&b'.'), Some(&b)) => b == SEP,
+			_ => false,
+		}
+	}
+
+	// parse a given byte sequence into the corresponding path component
+	fn parse_single_component<'b>(&self, comp: &'b [u8]) -> Option<Component<'b>> {
+		match comp {
+			b"." => None, // . components are normalized away, except at
+			// the beginning of a path, which is treated
+			// separately via `include_cur_dir`
+			b".." => Some(Component::ParentDir),
+			b"" => None,
+			_ => Some(Component::Normal(OsStr::from_bytes(comp))),
+		}
+	}
+
+	// parse a component from the left, saying how many bytes to consume to
+	// reach the next component
+	fn parse_next_component(&self) -> (usize, Option<Component<'a>>) {
+		debug_assert!(self.front == State::Body);
+		let (extra, comp) = match self.path.iter().position(|&b| b == SEP) {
+			None => (0, self.path),
+			Some(i) => (1, &self.path[..i]),
+		};
+		(comp.len() + extra, self.parse_single_component(comp))
+	}
+
+	// parse a component from the right, saying how many bytes to consume to
+	// reach the next component
+	fn parse_next_component_back(&self) -> (usize, Option<Component<'a>>) {
+		debug_assert!(self.back == State::Body);
+		let start = self.len_before_body();
+		let (extra, comp) = match self.path[start..].iter().rposition(|&b| b == SEP) {
+			None => (0, &self.path[start..]),
+			Some(i) => (1, &self.path[start + i + 1..]),
+		};
+		(comp.len() + extra, self.parse_single_component(comp))
+	}
+
+	// trim away repeated separators (i.e., empty components) on the left
+	fn trim_left(&mut self) {
+		while !self.path.is_empty() {
+			let (size, comp) = self.parse_next_component();
+			if comp.is_some() {
+				return;
+			} else {
+				self.path = &self.path[size..];
+			}
+		}
+	}
+
+	// trim away repeated separators (i.e., empty components) on the right
+	fn trim_right(&mut self) {
+		while self.path.len() > self.len_before_body() {
+			let (size, comp) = self.parse_next_component_back();
+			if comp.is_some() {
+				return;
+			} else {
+				self.path = &self.path[..self.path.len() - size];
+			}
+		}
+	}
+}
+
+impl AsRef<Path> for Components<'_> {
+	fn as_ref(&self) -> &Path {
+		self.as_path()
+	}
+}
+
+impl AsRef<OsStr> for Components<'_> {
+	fn as_ref(&self) -> &OsStr {
+		self.as_path().as_os_str()
+	}
+}
+
+impl fmt::Debug for Components<'_> {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		struct DebugHelper<'a>(&'a Path);
+
+		impl fmt::Debug for DebugHelper<'_> {
+			fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+				f.debug_list().entries(self.0.components()).finish()
+			}
+		}
+
+		f.debug_tuple("Components")
+			.field(&DebugHelper(self.as_path()))
+			.finish()
+	}
+}
+
+impl<'a> Iterator for Components<'a> {
+	type Item = Component<'a>;
+
+	fn next(&mut self) -> Option<Component<'a>> {
+		while !self.finished() {
+			match self.front {
+				State::StartDir => {
+					self.front = State::Body;
+					if self.has_physical_root {
+						debug_assert!(!self.path.is_empty());
+						self.path = &self.path[1..];
+						return Some(Component::RootDir);
+					} else if self.include_cur_dir() {
+						debug_assert!(!self.path.is_empty());
+						self.path = &self.path[1..];
+						return Some(Component::CurDir);
+					}
+				}
+				State::Body if !self.path.is_empty() => {
+					let (size, comp) = self.parse_next_component();
+					self.path = &self.path[size..];
+					if comp.is_some() {
+						return comp;
+					}
+				}
+				State::Body => {
+					self.front = State::Done;
+				}
+				State::Done => unreachable!(),
+			}
+		}
+		None
+	}
+}
+
+impl<'a> DoubleEndedIterator for Components<'a> {
+	fn next_back(&mut self) -> Option<Component<'a>> {
+		while !self.finished() {
+			match self.back {
+				State::Body if self.path.len() > self.len_before_body() => {
+					let (size, comp) = self.parse_next_component_back();
+					self.path = &self.path[..self.path.len() - size];
+					if comp.is_some() {
+						return comp;
+					}
+				}
+				State::Body => {
+					self.back = State::StartDir;
+				}
+				State::StartDir => {
+					self.back = State::Done;
+					if self.has_physical_root {
+						self.path = &self.path[..self.path.len() - 1];
+						return Some(Component::RootDir);
+					} else if self.include_cur_dir() {
+						self.path = &self.path[..self.path.len() - 1];
+						return Some(Component::CurDir);
+					}
+				}
+				State::Done => unreachable!(),
+			}
+		}
+		None
+	}
+}
+
+impl FusedIterator for Components<'_> {}
+
+impl<'a> PartialEq for Components<'a> {
+	fn eq(&self, other: &Components<'a>) -> bool {
+		Iterator::eq(self.clone(), other.clone())
+	}
+}
+
+impl Eq for Components<'_> {}
+
+impl<'a> PartialOrd for Components<'a> {
+	fn partial_cmp(&self, other: &Components<'a>) -> Option<cmp::Ordering> {
+		Some(self.cmp(other))
+	}
+}
+
+impl Ord for Components<'_> {
+	fn cmp(&self, other: &Self) -> cmp::Ordering {
+		Iterator::cmp(self.clone(), other.clone())
+	}
+}
+
+/// A slice of a path (akin to [`str`]).
+///
+/// This type supports a number of operations for inspecting a path, including
+/// breaking the path into its components (separated by `/`), extracting the
+/// file name, determining whether the path is absolute, and so on.
+///
+/// This is an *unsized* type, meaning that it must always be used behind a
+/// pointer like `&` or [`Box`]. For an owned version of this type,
+/// see [`PathBuf`].
+///
+/// More details about the overall approach can be found in
+/// the [module documentation](self).
+///
+/// # Examples
+///
+/// ```
+/// use embedded_ffi::{OsStr, Path};
+///
+/// let path = Path::new("./foo/bar.txt");
+///
+/// let parent = path.parent();
+/// assert_eq!(parent, Some(Path::new("./foo")));
+///
+/// let file_stem = path.file_stem();
+/// assert_eq!(file_stem, Some(OsStr::new("bar")));
+///
+/// let extension = path.extension();
+/// assert_eq!(extension, Some(OsStr::new("txt")));
+/// ```
+///
+/// [`Box`]: alloc::boxed::Box
+/// [`PathBuf`]: crate::PathBuf
+pub struct Path {
+	inner: OsStr,
+}
+
+impl Path {
+	/// Directly wraps a string slice as a `Path` slice.
+	///
+	/// This is a cost-free conversion.
+	///
+	/// # Examples
+	///
+	/// ```
+	/// use embedded_ffi::Path;
+	///
+	/// Path::new("foo.txt");
+	/// ```
+	///
+	/// You can create `Path`s from `String`s, or even other `Path`s:
+	///
+	/// ```
+	/// use embedded_ffi::Path;
+	///
+	/// let string = "foo.txt";
+	/// let from_string = Path::new(&string);
+	/// let from_path = Path::new(&from_string);
+	/// assert_eq!(from_string, from_path);
+	/// ```
+	pub fn new<S: AsRef<OsStr> + ?Sized>(s: &S) -> &Path {
+		unsafe { &*(s.as_ref() as *const OsStr as *const Path) }
+	}
+
+	fn from_bytes(bytes: &[u8]) -> &Path {
+		Path::new(OsStr::from_bytes(bytes))
+	}
+
+	fn as_bytes(&self) -> &[u8] {
+		self.inner.as_bytes()
+	}
+
+	/// Yields the underlying [`OsStr`] slice.
+	///
+	/// # Examples
+	///
+	/// ```
+	/// use embedded_ffi::{OsStr, Path};
+	///
+	/// let os_str = Path::new("foo.txt").as_os_str();
+	/// assert_eq!(os_str, OsStr::new("foo.txt"));
+	/// ```
+	pub fn as_os_str(&self) -> &OsStr {
+		&self.inner
+	}
+
+	/// Yields a [`&str`] slice if the `Path` is valid unicode.
+	///
+	/// This conversion may entail doing a check for UTF-8 validity.
+	///
+	/// [`&str`]: str
+	///
+	/// # Examples
+	///
+	/// ```
+	/// use embedded_ffi::Path;
+	///
+	/// let path = Path::new("foo.txt");
+	/// assert_eq!(path.to_str(), Some("foo.txt"));
+	/// ```
+	pub fn to_str(&self) -> Option<&str> {
+		self.inner.to_str()
+	}
+
+	/// Returns an object that implements [`Display`] for safely printing
+	/// paths that may contain non-Unicode data.
+	///
+	/// [`Display`]: fmt::Display
+	///
+	/// # Examples
+	///
+	/// ```
+	/// use embedded_ffi::Path;
+	///
+	/// let path = Path::new("/tmp/foo.rs");
+	/// assert_eq!(format!("{}", path.display()), "/tmp/foo.rs");
+	/// ```
+	pub fn display(&self) -> Display<'_> {
+		self.inner.display()
+	}
+
+	/// Converts a `Path` to a [`Cow<str>`].
+	///
+	/// Any non-Unicode sequences are replaced with
+	/// [`U+FFFD REPLACEMENT CHARACTER`][U+FFFD].
+	///
+	/// [U+FFFD]: core::char::REPLACEMENT_CHARACTER
+	#[cfg(feature = "alloc")]
+	pub fn to_string_lossy(&self) -> Cow<'_, str> {
+		self.inner.to_string_lossy()
+	}
+
+	/// Converts a `Path` to an owned [`PathBuf`].
+	///
+	/// # Examples
+	///
+	/// ```
+	/// use embedded_ffi::Path;
+	///
+	/// let path_buf = Path::new("foo.txt").to_path_buf();
+	/// assert_eq!(path_buf, embedded_ffi::PathBuf::from("foo.txt"));
+	/// ```
+	#[cfg(feature = "alloc")]
+	pub fn to_path_buf(&self) -> PathBuf {
+		PathBuf::from(self.inner.to_os_string())
+	}
+
+	/// Returns `true` if the `Path` is absolute, i.e., if it starts with `/`.
+	///
+	/// # Examples
+	///
+	/// ```
+	/// use embedded_ffi::Path;
+	///
+	/// assert!(Path::new("/etc/passwd").is_absolute());
+	/// assert!(!Path::new("foo.txt").is_absolute());
+	/// ```
+	pub fn is_absolute(&self) -> bool {
+		self.has_root()
+	}
+
+	/// Returns `true` if the `Path` is relative, i.e., not absolute.
+	///
+	/// See [`is_absolute`]'s documentation for more details.
+	///
+	/// # Examples
+	///
+	/// ```
+	/// use embedded_ffi::Path;
+	///
+	/// assert!(Path::new("foo.txt").is_relative());
+	/// ```
+	///
+	/// [`is_absolute`]: Path::is_absolute
+	pub fn is_relative(&self) -> bool {
+		!self.is_absolute()
+	}
+
+	/// Returns `true` if the `Path` has a root.
+	///
+	/// On Unix, a path has a root if it begins with `/`, which is the same
+	/// as being absolute.
+	///
+	/// # Examples
+	///
+	/// ```
+	/// use embedded_ffi::Path;
+	///
+	/// assert!(Path::new("/etc/passwd").has_root());
+	/// ```
+	pub fn has_root(&self) -> bool {
+		self.components().has_root()
+	}
+
+	/// Returns the `Path` without its final component, if there is one.
+	///
+	/// Returns [`None`] if the path terminates in a root or is empty.
+	///
+	/// # Examples
+	///
+	/// ```
+	/// use embedded_ffi::Path;
+	///
+	/// let path = Path::new("/foo/bar");
+	/// let parent = path.parent().unwrap();
+	/// assert_eq!(parent, Path::new("/foo"));
+	///
+	/// let grand_parent = parent.parent().unwrap();
+	/// assert_eq!(grand_parent, Path::new("/"));
+	/// assert_eq!(grand_parent.parent(), None);
+	/// ```
+	pub fn parent(&self) -> Option<&Path> {
+		let mut comps = self.components();
+		let comp = comps.next_back();
+		comp.and_then(|p| match p {
+			Component::Normal(_) | Component::CurDir | Component::ParentDir => {
+				Some(comps.as_path())
+			}
+			_ => None,
+		})
+	}
+
+	/// Returns the final component of the `Path`, if there is one.
+	///
+	/// If the path is a normal file, this is the file name. If it's the path
+	/// of a directory, this is the directory name.
+	///
+	/// Returns [`None`] if the path terminates in `..`.
+	///
+	/// # Examples
+	///
+	/// ```
+	/// use embedded_ffi::{OsStr, Path};
+	///
+	/// assert_eq!(Some(OsStr::new("bin")), Path::new("/usr/bin/").file_name());
+	/// assert_eq!(Some(OsStr::new("foo.txt")), Path::new("tmp/foo.txt").file_name());
+	/// assert_eq!(None, Path::new("foo.txt/..").file_name());
+	/// assert_eq!(None, Path::new("/").file_name());
+	/// ```
+	pub fn file_name(&self) -> Option<&OsStr> {
+		self.components().next_back().and_then(|p| match p {
+			Component::Normal(p) => Some(p),
+			_ => None,
+		})
+	}
+
+	/// Returns a path that, when joined onto `base`, yields `self`.
+	///
+	/// # Errors
+	///
+	/// If `base` is not a prefix of `self` (i.e., [`starts_with`]
+	/// returns `false`), returns [`Err`].
+	///
+	/// [`starts_with`]: Path::starts_with
+	///
+	/// # Examples
+	///
+	/// ```
+	/// use embedded_ffi::Path;
+	///
+	/// let path = Path::new("/test/haha/foo.txt");
+	///
+	/// assert_eq!(path.strip_prefix("/"), Ok(Path::new("test/haha/foo.txt")));
+	/// assert_eq!(path.strip_prefix("/test"), Ok(Path::new("haha/foo.txt")));
+	/// assert_eq!(path.strip_prefix("/test/"), Ok(Path::new("haha/foo.txt")));
+	/// assert_eq!(path.strip_prefix("/test/haha/foo.txt"), Ok(Path::new("")));
+	///
+	/// assert!(path.strip_prefix("test").is_err());
+	/// assert!(path.strip_prefix("/haha").is_err());
+	/// ```
+	pub fn strip_prefix<P: AsRef<Path>>(&self, base: P) -> Result<&Path, StripPrefixError> {
+		self._strip_prefix(base.as_ref())
+	}
+
+	fn _strip_prefix(&self, base: &Path) -> Result<&Path, StripPrefixError> {
+		iter_after(self.components(), base.components())
+			.map(|c| c.as_path())
+			.ok_or(StripPrefixError(()))
+	}
+
+	/// Determines whether `base` is a prefix of `self`.
+	///
+	/// Only considers whole path components to match.
+	///
+	/// # Examples
+	///
+	/// ```
+	/// use embedded_ffi::Path;
+	///
+	/// let path = Path::new("/etc/passwd");
+	///
+	/// assert!(path.starts_with("/etc"));
+	/// assert!(path.starts_with("/etc/"));
+	/// assert!(path.starts_with("/etc/passwd"));
+	///
+	/// assert!(!path.starts_with("/e"));
+	/// ```
+	pub fn starts_with<P: AsRef<Path>>(&self, base: P) -> bool {
+		self._starts_with(base.as_ref())
+	}
+
+	fn _starts_with(&self, base: &Path) -> bool {
+		iter_after(self.components(), base.components()).is_some()
+	}
+
+	/// Extracts the stem (non-extension) portion of [`file_name`].
+	///
+	/// The stem is:
+	///
+	/// * [`None`], if there is no file name;
+	/// * The entire file name if there is no embedded `.`;
+	/// * The entire file name if the file name begins with `.` and has no other `.`s within;
+	/// * Otherwise, the portion of the file name before the final `.`
+	///
+	/// [`file_name`]: Path::file_name
+	///
+	/// # Examples
+	///
+	/// ```
+	/// use embedded_ffi::{OsStr, Path};
+	///
+	/// assert_eq!(Some(OsStr::new("foo")), Path::new("foo.rs").file_stem());
+	/// assert_eq!(Some(OsStr::new("foo.tar")), Path::new("foo.tar.gz").file_stem());
+	/// ```
+	pub fn file_stem(&self) -> Option<&OsStr> {
+		self.file_name()
+			.map(|name| rsplit_file_at_dot(name.as_bytes()))
+			.and_then(|(before, _)| before.map(OsStr::from_bytes))
+	}
+
+	/// Extracts the extension of [`file_name`], if possible.
+	///
+	/// The extension is:
+	///
+	/// * [`None`], if there is no file name;
+	/// * [`None`], if there is no embedded `.`;
+	/// * [`None`], if the file name begins with `.` and has no other `.`s within;
+	/// * Otherwise, the portion of the file name after the final `.`
+	///
+	/// [`file_name`]: Path::file_name
+	///
+	/// # Examples
+	///
+	/// ```
+	/// use embedded_ffi::{OsStr, Path};
+	///
+	/// assert_eq!(Some(OsStr::new("rs")), Path::new("foo.rs").extension());
+	/// assert_eq!(Some(OsStr::new("gz")), Path::new("foo.tar.gz").extension());
+	/// assert_eq!(None, Path::new(".profile").extension());
+	/// ```
+	pub fn extension(&self) -> Option<&OsStr> {
+		self.file_name()
+			.map(|name| rsplit_file_at_dot(name.as_bytes()))
+			.and_then(|(_, after)| after.map(OsStr::from_bytes))
+	}
+
+	/// Creates an owned [`PathBuf`] with `path` adjoined to `self`.
+	///
+	/// See [`PathBuf::push`] for more details on what it means to adjoin a path.
+	///
+	/// # Examples
+	///
+	/// ```
+	/// use embedded_ffi::{Path, PathBuf};
+	///
+	/// assert_eq!(Path::new("/etc").join("passwd"), PathBuf::from("/etc/passwd"));
+	/// assert_eq!(Path::new("/etc").join("/bin/sh"), PathBuf::from("/bin/sh"));
+	/// ```
+	#[cfg(feature = "alloc")]
+	pub fn join<P: AsRef<Path>>(&self, path: P) -> PathBuf {
+		let mut buf = self.to_path_buf();
+		buf.push(path);
+		buf
+	}
+
+	/// Creates an owned [`PathBuf`] like `self` but with the given file name.
+	///
+	/// See [`PathBuf::set_file_name`] for more details.
+	///
+	/// # Examples
+	///
+	/// ```
+	/// use embedded_ffi::{Path, PathBuf};
+	///
+	/// let path = Path::new("/tmp/foo.txt");
+	/// assert_eq!(path.with_file_name("bar.txt"), PathBuf::from("/tmp/bar.txt"));
+	/// ```
+	#[cfg(feature = "alloc")]
+	pub fn with_file_name<S: AsRef<OsStr>>(&self, file_name: S) -> PathBuf {
+		let mut buf = self.to_path_buf();
+		buf.set_file_name(file_name);
+		buf
+	}
+
+	/// Creates an owned [`PathBuf`] like `self` but with the given extension.
+	///
+	/// See [`PathBuf::set_extension`] for more details.
+	///
+	/// # Examples
+	///
+	/// ```
+	/// use embedded_ffi::{Path, PathBuf};
+	///
+	/// let path = Path::new("foo.rs");
+	/// assert_eq!(path.with_extension("txt"), PathBuf::from("foo.txt"));
+	/// ```
+	#[cfg(feature = "alloc")]
+	pub fn with_extension<S: AsRef<OsStr>>(&self, extension: S) -> PathBuf {
+		let mut buf = self.to_path_buf();
+		buf.set_extension(extension);
+		buf
+	}
+
+	/// Produces an iterator over the [`Component`]s of the path.
+	///
+	/// When parsing the path, there is a small amount of normalization:
+	///
+	/// * Repeated separators are ignored, so `a/b` and `a//b` both have
+	///   `a` and `b` as components.
+	///
+	/// * Occurrences of `.` are normalized away, except if they are at the
+	///   beginning of the path. For example, `a/./b`, `a/b/`, `a/b/.` and
+	///   `a/b` all have `a` and `b` as components, but `./a/b` starts with
+	///   an additional [`CurDir`] component.
+	///
+	/// * A trailing slash is normalized away, `/a/b` and `/a/b/` are equivalent.
+	///
+	/// Note that no other normalization takes place; in particular, `a/c`
+	/// and `a/b/../c` are distinct, to account for the possibility that `b`
+	/// is a symbolic link (so its parent isn't `a`).
+	///
+	/// # Examples
+	///
+	/// ```
+	/// use embedded_ffi::path::{Component, Path};
+	/// use embedded_ffi::OsStr;
+	///
+	/// let mut components = Path::new("/tmp/foo.txt").components();
+	///
+	/// assert_eq!(components.next(), Some(Component::RootDir));
+	/// assert_eq!(components.next(), Some(Component::Normal(OsStr::new("tmp"))));
+	/// assert_eq!(components.next(), Some(Component::Normal(OsStr::new("foo.txt"))));
+	/// assert_eq!(components.next(), None)
+	/// ```
+	///
+	/// [`CurDir`]: Component::CurDir
+	pub fn components(&self) -> Components<'_> {
+		let path = self.as_bytes();
+		Components {
+			path,
+			has_physical_root: has_physical_root(path),
+			front: State::StartDir,
+			back: State::Body,
+		}
+	}
+
+	/// Lexically normalizes the path, without touching any filesystem.
+	///
+	/// This resolves `.` and `..` components and removes repeated and
+	/// trailing separators, purely by looking at the path:
+	///
+	/// * `.` components are removed.
+	/// * A `..` component removes the preceding normal component. At the
+	///   start of an absolute path it is dropped, since the parent of `/` is
+	///   `/`; at the start of a relative path it is kept.
+	/// * A non-empty path that collapses to nothing becomes `.`.
+	///
+	/// Symbolic links are not resolved, so the result may refer to a
+	/// different file than the original if a component removed by `..` was
+	/// a link.
+	///
+	/// If the path is already in normal form, it is returned as-is without
+	/// allocating. For an allocation-free variant, see
+	/// [`normalize_lexically_into`].
+	///
+	/// [`normalize_lexically_into`]: Path::normalize_lexically_into
+	///
+	/// # Examples
+	///
+	/// ```
+	/// use embedded_ffi::Path;
+	/// use std::borrow::Cow;
+	///
+	/// let path = Path::new("/boot//cfg/./../kernel.img");
+	/// assert_eq!(path.normalize_lexically(), Path::new("/boot/kernel.img"));
+	/// assert_eq!(Path::new("a/b/../..").normalize_lexically(), Path::new("."));
+	/// assert_eq!(Path::new("/..").normalize_lexically(), Path::new("/"));
+	/// assert_eq!(Path::new("../a").normalize_lexically(), Path::new("../a"));
+	///
+	/// assert!(matches!(Path::new("/etc/fstab").normalize_lexically(), Cow::Borrowed(_)));
+	/// ```
+	#[cfg(feature = "alloc")]
+	pub fn normalize_lexically(&self) -> Cow<'_, Path> {
+		if is_normalized(self.as_bytes()) {
+			return Cow::Borrowed(self);
+		}
+		let mut out = Vec::with_capacity(self.as_bytes().len());
+		// Writing into a `Vec` cannot fail
+		let _ = normalize_into(self, &mut out);
+		Cow::Owned(PathBuf::from(OsString::from_vec(out)))
+	}
+
+	/// Lexically normalizes the path into `buf`, without allocating.
+	///
+	/// This performs the same normalization as [`normalize_lexically`], but
+	/// always writes the result into the start of `buf` and returns it.
+	///
+	/// [`normalize_lexically`]: Path::normalize_lexically
+	///
+	/// # Errors
+	///
+	/// Returns [`CapacityError`] if `buf` is too short to hold the result.
+	/// The normalized form is never longer than the original path, so a
+	/// buffer of `self.as_os_str().len()` bytes is always sufficient.
+	///
+	/// # Examples
+	///
+	/// ```
+	/// use embedded_ffi::Path;
+	///
+	/// let mut buf = [0; 32];
+	/// let path = Path::new("cfg/../boot/./kernel.img");
+	/// let normalized = path.normalize_lexically_into(&mut buf).unwrap();
+	/// assert_eq!(normalized, Path::new("boot/kernel.img"));
+	///
+	/// assert!(path.normalize_lexically_into(&mut [0; 4]).is_err());
+	/// ```
+	pub fn normalize_lexically_into<'b>(
+		&self,
+		buf: &'b mut [u8],
+	) -> Result<&'b Path, CapacityError> {
+		let mut out = SliceSink { buf, len: 0 };
+		normalize_into(self, &mut out)?;
+		let SliceSink { buf, len } = out;
+		Ok(Path::from_bytes(&buf[..len]))
+	}
+
+	/// Converts a [`Box<Path>`] into a [`PathBuf`] without copying or
+	/// allocating.
+	#[cfg(feature = "alloc")]
+	pub fn into_path_buf(self: Box<Path>) -> PathBuf {
+		let rw = Box::into_raw(self) as *mut OsStr;
+		let inner = unsafe { Box::from_raw(rw) };
+		PathBuf {
+			inner: OsString::from(inner),
+		}
+	}
+}
+
+impl fmt::Debug for Path {
+	fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+		fmt::Debug::fmt(&self.inner, formatter)
+	}
+}
+
+impl PartialEq for Path {
+	fn eq(&self, other: &Path) -> bool {
+		self.components() == other.components()
+	}
+}
+
+impl Eq for Path {}
+
+impl PartialOrd for Path {
+	fn partial_cmp(&self, other: &Path) -> Option<cmp::Ordering> {
+		Some(self.cmp(other))
+	}
+}
+
+impl Ord for Path {
+	fn cmp(&self, other: &Path) -> cmp::Ordering {
+		self.components().cmp(other.components())
+	}
+}
+
+impl Hash for Path {
+	fn hash<H: Hasher>(&self, h: &mut H) {
+		for component in self.components() {
+			component.hash(h);
+		}
+	}
+}
+
+impl AsRef<OsStr> for Path {
+	fn as_ref(&self) -> &OsStr {
+		&self.inner
+	}
+}
+
+impl AsRef<Path> for Path {
+	fn as_ref(&self) -> &Path {
+		self
+	}
+}
+
+impl AsRef<Path> for OsStr {
+	fn as_ref(&self) -> &Path {
+		Path::new(self)
+	}
+}
+
+impl AsRef<Path> for str {
+	fn as_ref(&self) -> &Path {
+		Path::new(self)
+	}
+}
+
+#[cfg(feature = "alloc")]
+impl AsRef<Path> for OsString {
+	fn as_ref(&self) -> &Path {
+		Path::new(self)
+	}
+}
+
+#[cfg(feature = "alloc")]
+impl AsRef<Path> for String {
+	fn as_ref(&self) -> &Path {
+		Path::new(self)
+	}
+}
+
+impl Default for &Path {
+	/// Creates an empty `Path`.
+	fn default() -> Self {
+		Path::new("")
+	}
+}
+
+/// An owned, mutable path (akin to [`String`]).
+///
+/// This type provides methods like [`push`] and [`set_extension`] that mutate
+/// the path in place. It also implements [`Deref`] to [`Path`], meaning that
+/// all methods on [`Path`] slices are available on `PathBuf` values as well.
+///
+/// More details about the overall approach can be found in
+/// the [module documentation](self).
+///
+/// # Examples
+///
+/// ```
+/// use embedded_ffi::PathBuf;
+///
+/// let mut path = PathBuf::from("/");
+/// path.push("etc");
+/// path.push("passwd");
+/// assert_eq!(path, PathBuf::from("/etc/passwd"));
+/// ```
+///
+/// [`push`]: PathBuf::push
+/// [`set_extension`]: PathBuf::set_extension
+/// [`Deref`]: core::ops::Deref
+#[cfg(feature = "alloc")]
+#[derive(Clone)]
+pub struct PathBuf {
+	inner: OsString,
+}
+
+#[cfg(feature = "alloc")]
+impl PathBuf {
+	fn as_mut_vec(&mut self) -> &mut Vec<u8> {
+		self.inner.as_mut_vec()
+	}
+
+	/// Allocates an empty `PathBuf`.
+	///
+	/// # Examples
+	///
+	/// ```
+	/// use embedded_ffi::PathBuf;
+	///
+	/// let path = PathBuf::new();
+	/// ```
+	pub fn new() -> PathBuf {
+		PathBuf {
+			inner: OsString::new(),
+		}
+	}
+
+	/// Creates a new `PathBuf` with a given capacity used to create the
+	/// internal [`OsString`]. See [`with_capacity`] defined on [`OsString`].
+	///
+	/// [`with_capacity`]: OsString::with_capacity
+	pub fn with_capacity(capacity: usize) -> PathBuf {
+		PathBuf {
+			inner: OsString::with_capacity(capacity),
+		}
+	}
+
+	/// Coerces to a [`Path`] slice.
+	///
+	/// # Examples
+	///
+	/// ```
+	/// use embedded_ffi::{Path, PathBuf};
+	///
+	/// let p = PathBuf::from("/test");
+	/// assert_eq!(Path::new("/test"), p.as_path());
+	/// ```
+	pub fn as_path(&self) -> &Path {
+		self
+	}
+
+	/// Extends `self` with `path`.
+	///
+	/// If `path` is absolute, it replaces the current path.
+	///
+	/// Otherwise, `path` is appended to `self`, inserting a `/` between them
+	/// unless `self` is empty or already ends with one.
+	///
+	/// # Examples
+	///
+	/// ```
+	/// use embedded_ffi::PathBuf;
+	///
+	/// let mut path = PathBuf::from("/tmp");
+	/// path.push("file.bk");
+	/// assert_eq!(path, PathBuf::from("/tmp/file.bk"));
+	///
+	/// let mut path = PathBuf::from("/tmp");
+	/// path.push("/etc");
+	/// assert_eq!(path, PathBuf::from("/etc"));
+	/// ```
+	pub fn push<P: AsRef<Path>>(&mut self, path: P) {
+		self._push(path.as_ref())
+	}
+
+	fn _push(&mut self, path: &Path) {
+		let need_sep = matches!(self.as_bytes().last(), Some(&b) if b != SEP);
+
+		if path.is_absolute() {
+			self.as_mut_vec().clear();
+		} else if need_sep {
+			self.inner.push("/");
+		}
+
+		self.inner.push(path.as_os_str());
+	}
+
+	/// Truncates `self` to [`self.parent`].
+	///
+	/// Returns `false` and does nothing if [`self.parent`] is [`None`].
+	/// Otherwise, returns `true`.
+	///
+	/// [`self.parent`]: Path::parent
+	///
+	/// # Examples
+	///
+	/// ```
+	/// use embedded_ffi::{Path, PathBuf};
+	///
+	/// let mut p = PathBuf::from("/test/test.rs");
+	///
+	/// p.pop();
+	/// assert_eq!(Path::new("/test"), p);
+	/// p.pop();
+	/// assert_eq!(Path::new("/"), p);
+	/// ```
+	pub fn pop(&mut self) -> bool {
+		match self.parent().map(|p| p.as_bytes().len()) {
+			Some(len) => {
+				self.as_mut_vec().truncate(len);
+				true
+			}
+			None => false,
+		}
+	}
+
+	/// Updates [`self.file_name`] to `file_name`.
+	///
+	/// If [`self.file_name`] was [`None`], this is equivalent to pushing
+	/// `file_name`.
+	///
+	/// Otherwise it is equivalent to calling [`pop`] and then pushing
+	/// `file_name`.
+	///
+	/// [`self.file_name`]: Path::file_name
+	/// [`pop`]: PathBuf::pop
+	///
+	/// # Examples
+	///
+	/// ```
+	/// use embedded_ffi::PathBuf;
+	///
+	/// let mut buf = PathBuf::from("/");
+	/// assert!(buf.file_name() == None);
+	/// buf.set_file_name("bar");
+	/// assert!(buf == PathBuf::from("/bar"));
+	/// assert!(buf.file_name().is_some());
+	/// buf.set_file_name("baz.txt");
+	/// assert!(buf == PathBuf::from("/baz.txt"));
+	/// ```
+	pub fn set_file_name<S: AsRef<OsStr>>(&mut self, file_name: S) {
+		self._set_file_name(file_name.as_ref())
+	}
+
+	fn _set_file_name(&mut self, file_name: &OsStr) {
+		if self.file_name().is_some() {
+			let popped = self.pop();
+			debug_assert!(popped);
+		}
+		self.push(file_name);
+	}
+
+	/// Updates [`self.extension`] to `extension`.
+	///
+	/// Returns `false` and does nothing if [`self.file_name`] is [`None`],
+	/// returns `true` and updates the extension otherwise.
+	///
+	/// If [`self.extension`] is [`None`], the extension is added; otherwise
+	/// it is replaced.
+	///
+	/// [`self.file_name`]: Path::file_name
+	/// [`self.extension`]: Path::extension
+	///
+	/// # Examples
+	///
+	/// ```
+	/// use embedded_ffi::{Path, PathBuf};
+	///
+	/// let mut p = PathBuf::from("/feel/the");
+	///
+	/// p.set_extension("force");
+	/// assert_eq!(Path::new("/feel/the.force"), p.as_path());
+	///
+	/// p.set_extension("dark_side");
+	/// assert_eq!(Path::new("/feel/the.dark_side"), p.as_path());
+	/// ```
+	pub fn set_extension<S: AsRef<OsStr>>(&mut self, extension: S) -> bool {
+		self._set_extension(extension.as_ref())
+	}
+
+	fn _set_extension(&mut self, extension: &OsStr) -> bool {
+		let end_file_stem = match self.file_stem() {
+			None => return false,
+			Some(f) => {
+				let start = self.as_bytes().as_ptr() as usize;
+				f.as_bytes().as_ptr() as usize - start + f.len()
+			}
+		};
+
+		let v = self.as_mut_vec();
+		v.truncate(end_file_stem);
+
+		let new = extension.as_bytes();
+		if !new.is_empty() {
+			v.reserve_exact(new.len() + 1);
+			v.push(b'.');
+			v.extend_from_slice(new);
+		}
+
+		true
+	}
+
+	/// Consumes the `PathBuf`, yielding its internal [`OsString`] storage.
+	///
+	/// # Examples
+	///
+	/// ```
+	/// use embedded_ffi::PathBuf;
+	///
+	/// let p = PathBuf::from("/the/head");
+	/// let os_str = p.into_os_string();
+	/// ```
+	pub fn into_os_string(self) -> OsString {
+		self.inner
+	}
+
+	/// Converts this `PathBuf` into a [boxed](Box) [`Path`].
+	pub fn into_boxed_path(self) -> Box<Path> {
+		let rw = Box::into_raw(self.inner.into_boxed_os_str()) as *mut Path;
+		unsafe { Box::from_raw(rw) }
+	}
+
+	/// Invokes [`capacity`] on the underlying instance of [`OsString`].
+	///
+	/// [`capacity`]: OsString::capacity
+	pub fn capacity(&self) -> usize {
+		self.inner.capacity()
+	}
+
+	/// Invokes [`clear`] on the underlying instance of [`OsString`].
+	///
+	/// [`clear`]: OsString::clear
+	pub fn clear(&mut self) {
+		self.inner.clear()
+	}
+
+	/// Invokes [`reserve`] on the underlying instance of [`OsString`].
+	///
+	/// [`reserve`]: OsString::reserve
+	pub fn reserve(&mut self, additional: usize) {
+		self.inner.reserve(additional)
+	}
+
+	/// Invokes [`shrink_to_fit`] on the underlying instance of [`OsString`].
+	///
+	/// [`shrink_to_fit`]: OsString::shrink_to_fit
+	pub fn shrink_to_fit(&mut self) {
+		self.inner.shrink_to_fit()
+	}
+}
+
+#[cfg(feature = "alloc")]
+impl ops::Deref for PathBuf {
+	type Target = Path;
+
+	#[inline]
+	fn deref(&self) -> &Path {
+		Path::new(&self.inner)
+	}
+}
+
+#[cfg(feature = "alloc")]
+impl Default for PathBuf {
+	fn default() -> Self {
+		PathBuf::new()
+	}
+}
+
+#[cfg(feature = "alloc")]
+impl fmt::Debug for PathBuf {
+	fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+		fmt::Debug::fmt(&**self, formatter)
+	}
+}
+
+#[cfg(feature = "alloc")]
+impl<T: ?Sized + AsRef<OsStr>> From<&T> for PathBuf {
+	fn from(s: &T) -> PathBuf {
+		PathBuf::from(s.as_ref().to_os_string())
+	}
+}
+
+#[cfg(feature = "alloc")]
+impl From<OsString> for PathBuf {
+	/// Converts an [`OsString`] into a `PathBuf` without copying or allocating.
+	fn from(s: OsString) -> PathBuf {
+		PathBuf { inner: s }
+	}
+}
+
+#[cfg(feature = "alloc")]
+impl From<PathBuf> for OsString {
+	/// Converts a `PathBuf` into an [`OsString`] without copying or allocating.
+	fn from(path_buf: PathBuf) -> OsString {
+		path_buf.inner
+	}
+}
+
+#[cfg(feature = "alloc")]
+impl From<String> for PathBuf {
+	/// Converts a [`String`] into a `PathBuf` without copying or allocating.
+	fn from(s: String) -> PathBuf {
+		PathBuf::from(OsString::from(s))
+	}
+}
+
+#[cfg(feature = "alloc")]
+impl From<Box<Path>> for PathBuf {
+	/// Converts a `Box<Path>` into a `PathBuf` without copying or allocating.
+	fn from(boxed: Box<Path>) -> PathBuf {
+		boxed.into_path_buf()
+	}
+}
+
+#[cfg(feature = "alloc")]
+impl From<PathBuf> for Box<Path> {
+	/// Converts a `PathBuf` into a `Box<Path>` without copying or allocating.
+	fn from(p: PathBuf) -> Box<Path> {
+		p.into_boxed_path()
+	}
+}
+
+#[cfg(feature = "alloc")]
+impl<'a> From<&'a Path> for Cow<'a, Path> {
+	#[inline]
+	fn from(s: &'a Path) -> Cow<'a, Path> {
+		Cow::Borrowed(s)
+	}
+}
+
+#[cfg(feature = "alloc")]
+impl<'a> From<PathBuf> for Cow<'a, Path> {
+	#[inline]
+	fn from(s: PathBuf) -> Cow<'a, Path> {
+		Cow::Owned(s)
+	}
+}
+
+#[cfg(feature = "alloc")]
+impl<P: AsRef<Path>> core::iter::Extend<P> for PathBuf {
+	fn extend<I: IntoIterator<Item = P>>(&mut self, iter: I) {
+		iter.into_iter().for_each(move |p| self.push(p.as_ref()));
+	}
+}
+
+#[cfg(feature = "alloc")]
+impl<P: AsRef<Path>> core::iter::FromIterator<P> for PathBuf {
+	fn from_iter<I: IntoIterator<Item = P>>(iter: I) -> PathBuf {
+		let mut buf = PathBuf::new();
+		buf.extend(iter);
+		buf
+	}
+}
+
+#[cfg(feature = "alloc")]
+impl Borrow<Path> for PathBuf {
+	fn borrow(&self) -> &Path {
+		self
+	}
+}
+
+#[cfg(feature = "alloc")]
+impl ToOwned for Path {
+	type Owned = PathBuf;
+	fn to_owned(&self) -> PathBuf {
+		self.to_path_buf()
+	}
+}
+
+#[cfg(feature = "alloc")]
+impl PartialEq for PathBuf {
+	fn eq(&self, other: &PathBuf) -> bool {
+		self.as_path() == other.as_path()
+	}
+}
+
+#[cfg(feature = "alloc")]
+impl Eq for PathBuf {}
+
+#[cfg(feature = "alloc")]
+impl PartialOrd for PathBuf {
+	fn partial_cmp(&self, other: &PathBuf) -> Option<cmp::Ordering> {
+		Some(self.cmp(other))
+	}
+}
+
+#[cfg(feature = "alloc")]
+impl Ord for PathBuf {
+	fn cmp(&self, other: &PathBuf) -> cmp::Ordering {
+		self.as_path().cmp(other.as_path())
+	}
+}
+
+#[cfg(feature = "alloc")]
+impl Hash for PathBuf {
+	fn hash<H: Hasher>(&self, h: &mut H) {
+		self.as_path().hash(h)
+	}
+}
+
+#[cfg(feature = "alloc")]
+impl AsRef<OsStr> for PathBuf {
+	fn as_ref(&self) -> &OsStr {
+		&self.inner
+	}
+}
+
+#[cfg(feature = "alloc")]
+impl AsRef<Path> for PathBuf {
+	fn as_ref(&self) -> &Path {
+		self
+	}
+}
+
+#[cfg(feature = "alloc")]
+macro_rules! impl_cmp {
+	($lhs:ty, $rhs: ty) => {
+		impl<'a, 'b> PartialEq<$rhs> for $lhs {
+			#[inline]
+			fn eq(&self, other: &$rhs) -> bool {
+				<Path as PartialEq>::eq(self, other)
+			}
+		}
+
+		impl<'a, 'b> PartialEq<$lhs> for $rhs {
+			#[inline]
+			fn eq(&self, other: &$lhs) -> bool {
+				<Path as PartialEq>::eq(self, other)
+			}
+		}
+
+		impl<'a, 'b> PartialOrd<$rhs> for $lhs {
+			#[inline]
+			fn partial_cmp(&self, other: &$rhs) -> Option<cmp::Ordering> {
+				<Path as PartialOrd>::partial_cmp(self, other)
+			}
+		}
+
+		impl<'a, 'b> PartialOrd<$lhs> for $rhs {
+			#[inline]
+			fn partial_cmp(&self, other: &$lhs) -> Option<cmp::Ordering> {
+				<Path as PartialOrd>::partial_cmp(self, other)
+			}
+		}
+	};
+}
+
+#[cfg(feature = "alloc")]
+impl_cmp!(PathBuf, Path);
+#[cfg(feature = "alloc")]
+impl_cmp!(PathBuf, &'a Path);
+#[cfg(feature = "alloc")]
+impl_cmp!(Cow<'a, Path>, Path);
+#[cfg(feature = "alloc")]
+impl_cmp!(Cow<'a, Path>, &'b Path);
+#[cfg(feature = "alloc")]
+impl_cmp!(Cow<'a, Path>, PathBuf);
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn test_parent_and_file_name() {
+		let cases: &[(&str, Option<&str>, Option<&str>)] = &[
+			("", None, None),
+			("/", None, None),
+			("foo", Some(""), Some("foo")),
+			("/foo", Some("/"), Some("foo")),
+			("foo/bar", Some("foo"), Some("bar")),
+			("foo//bar//", Some("foo"), Some("bar")),
+			("/foo/..", Some("/foo"), None),
+			("..", Some(""), None),
+			(".", Some(""), None),
+			("foo/.", Some(""), Some("foo")),
+		];
+		for &(path, parent, file_name) in cases {
+			let path = Path::new(path);
+			assert_eq!(path.parent(), parent.map(Path::new), "parent of {:?}", path);
+			assert_eq!(
+				path.file_name(),
+				file_name.map(OsStr::new),
+				"file_name of {:?}",
+				path
+			);
+		}
+	}
+
+	#[test]
+	fn test_stem_and_extension() {
+		let cases: &[(&str, Option<&str>, Option<&str>)] = &[
+			("foo", Some("foo"), None),
+			("foo.", Some("foo"), Some("")),
+			(".foo", Some(".foo"), None),
+			("foo.bar.baz", Some("foo.bar"), Some("baz")),
+			("/a/b.c/", Some("b"), Some("c")),
+			("..", None, None),
+		];
+		for &(path, stem, extension) in cases {
+			let path = Path::new(path);
+			assert_eq!(path.file_stem(), stem.map(OsStr::new), "stem of {:?}", path);
+			assert_eq!(
+				path.extension(),
+				extension.map(OsStr::new),
+				"extension of {:?}",
+				path
+			);
+		}
+	}
+
+	#[test]
+	fn test_components() {
+		use Component::*;
+
+		let n = |s| Normal(OsStr::new(s));
+		let cases: &[(&str, &[Component<'_>])] = &[
+			("", &[]),
+			("/", &[RootDir]),
+			("//", &[RootDir]),
+			(".", &[CurDir]),
+			("./", &[CurDir]),
+			("./.", &[CurDir]),
+			("..", &[ParentDir]),
+			("a/./b", &[n("a"), n("b")]),
+			("a//b/", &[n("a"), n("b")]),
+			("a/b/.", &[n("a"), n("b")]),
+			("./a/../b", &[CurDir, n("a"), ParentDir, n("b")]),
+			("/./a", &[RootDir, n("a")]),
+			("//a//b//", &[RootDir, n("a"), n("b")]),
+			(".a/b", &[n(".a"), n("b")]),
+		];
+		for &(path, expected) in cases {
+			let path = Path::new(path);
+			assert!(path.components().eq(expected.iter().cloned()), "{:?}", path);
+			assert!(
+				path.components().rev().eq(expected.iter().rev().cloned()),
+				"reversed {:?}",
+				path
+			);
+		}
+	}
+
+	#[test]
+	fn test_components_as_path() {
+		let mut comps = Path::new("/a//b/./c/").components();
+		assert_eq!(comps.as_path(), Path::new("/a//b/./c"));
+		comps.next();
+		assert_eq!(comps.as_path().as_os_str(), "a//b/./c");
+		comps.next_back();
+		assert_eq!(comps.as_path().as_os_str(), "a//b");
+		comps.next();
+		comps.next();
+		assert_eq!(comps.as_path().as_os_str(), "");
+		assert_eq!(comps.next(), None);
+	}
+
+	#[test]
+	fn test_eq_ignores_redundant_separators() {
+		assert_eq!(Path::new("a//b/"), Path::new("a/b"));
+		assert_eq!(Path::new("a/./b"), Path::new("a/b"));
+		assert_ne!(Path::new("./a"), Path::new("a"));
+		assert_ne!(Path::new("/a"), Path::new("a"));
+	}
+
+	#[test]
+	fn test_strip_prefix() {
+		let path = Path::new("/usr/lib/libc.a");
+		assert_eq!(path.strip_prefix(""), Ok(path));
+		assert_eq!(path.strip_prefix("/usr"), Ok(Path::new("lib/libc.a")));
+		assert_eq!(path.strip_prefix("/usr/lib/"), Ok(Path::new("libc.a")));
+		assert!(path.strip_prefix("/usr/li").is_err());
+		assert!(path.strip_prefix("usr").is_err());
+		assert_eq!(path.strip_prefix("//usr/./lib"), Ok(Path::new("libc.a")));
+		assert!(path.starts_with("/"));
+		assert!(!Path::new("usr").starts_with("/"));
+	}
+
+	#[test]
+	fn test_normalize_lexically_into() {
+		let cases: &[(&str, &str)] = &[
+			("", ""),
+			(".", "."),
+			("./", "."),
+			("/", "/"),
+			("//..//.", "/"),
+			("a/..", "."),
+			("a/../..", ".."),
+			("../../a/b/..", "../../a"),
+			("./a//b/", "a/b"),
+			("/a/b/../../../c", "/c"),
+			("a/./b/../../c/.", "c"),
+		];
+		let mut buf = [0; 32];
+		for &(path, expected) in cases {
+			let normalized = Path::new(path).normalize_lexically_into(&mut buf);
+			assert_eq!(normalized, Ok(Path::new(expected)), "{:?}", path);
+			assert_eq!(
+				normalized.unwrap().as_os_str(),
+				expected,
+				"{:?} is not byte-identical",
+				path
+			);
+		}
+	}
+
+	#[test]
+	fn test_normalize_lexically_into_capacity() {
+		let path = Path::new("/a/b/../c");
+		assert_eq!(
+			path.normalize_lexically_into(&mut [0; 3]),
+			Err(CapacityError(()))
+		);
+		assert!(path.normalize_lexically_into(&mut [0; 4]).is_ok());
+		assert!(Path::new("a/..").normalize_lexically_into(&mut []).is_err());
+	}
+
+	#[cfg(feature = "alloc")]
+	#[test]
+	fn test_normalize_lexically() {
+		for &path in &["", ".", "/", "a", "/a/b", "../..", "../a", "../../a/b"] {
+			let path = Path::new(path);
+			match path.normalize_lexically() {
+				Cow::Borrowed(p) => assert_eq!(p.as_os_str(), path.as_os_str()),
+				Cow::Owned(p) => panic!("{:?} should be borrowed, got {:?}", path, p),
+			}
+		}
+		for &(path, expected) in &[("a/", "a"), ("./a", "a"), ("/..", "/"), ("a/..", ".")] {
+			match Path::new(path).normalize_lexically() {
+				Cow::Owned(p) => assert_eq!(p.as_os_str(), expected),
+				Cow::Borrowed(p) => panic!("{:?} should be owned, got {:?}", path, p),
+			}
+		}
+	}
+
+	#[cfg(feature = "alloc")]
+	#[test]
+	fn test_push_pop() {
+		let mut path = PathBuf::new();
+		path.push("foo");
+		path.push("bar/");
+		path.push("baz");
+		assert_eq!(path, Path::new("foo/bar/baz"));
+		assert!(path.pop());
+		assert_eq!(path, Path::new("foo/bar"));
+		path.push("/etc");
+		assert_eq!(path, Path::new("/etc"));
+		assert!(path.pop());
+		assert_eq!(path, Path::new("/"));
+		assert!(!path.pop());
+	}
+
+	#[cfg(feature = "alloc")]
+	#[test]
+	fn test_set_extension() {
+		let mut path = PathBuf::from("/tmp/foo.tar.gz");
+		assert!(path.set_extension("xz"));
+		assert_eq!(path, Path::new("/tmp/foo.tar.xz"));
+		assert!(path.set_extension(""));
+		assert_eq!(path, Path::new("/tmp/foo.tar"));
+
+		let mut path = PathBuf::from("/");
+		assert!(!path.set_extension("txt"));
+		assert_eq!(path, Path::new("/"));
+	}
+}
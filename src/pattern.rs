@@ -0,0 +1,204 @@
+//! The pattern API used by the search methods on [`OsStr`].
+//!
+//! All patterns match against the raw bytes of an [`OsStr`], so they work
+//! the same way regardless of whether the string is valid UTF-8.
+//!
+//! [`OsStr`]: crate::OsStr
+use core::ops::Range;
+
+use crate::OsStr;
+use crate::OsStrExt;
+
+/// A pattern that can be searched for in an [`OsStr`].
+///
+/// This is implemented for:
+///
+/// * `&str`, `&&str`, `&OsStr` and `&[u8]`, which match the exact byte
+///   sequence. An empty needle matches everywhere.
+/// * `char`, which matches its UTF-8 encoding.
+/// * `u8`, which matches a single byte.
+/// * `F: FnMut(u8) -> bool`, which matches a single byte for which the
+///   predicate returns `true`.
+///
+/// All positions are byte offsets into the haystack.
+///
+/// This trait is sealed and cannot be implemented outside of this crate.
+///
+/// [`OsStr`]: crate::OsStr
+pub trait Pattern: private::Sealed {
+	/// Returns the range of the first match in `haystack`, if any.
+	fn find_in(&mut self, haystack: &[u8]) -> Option<Range<usize>>;
+
+	/// Returns the range of the last match in `haystack`, if any.
+	fn rfind_in(&mut self, haystack: &[u8]) -> Option<Range<usize>>;
+
+	/// Returns the length of the match at the start of `haystack`, if the
+	/// pattern matches there.
+	fn prefix_len(&mut self, haystack: &[u8]) -> Option<usize>;
+
+	/// Returns the length of the match at the end of `haystack`, if the
+	/// pattern matches there.
+	fn suffix_len(&mut self, haystack: &[u8]) -> Option<usize>;
+}
+
+mod private {
+	use crate::OsStr;
+
+	pub trait Sealed {}
+
+	impl Sealed for &[u8] {}
+	impl Sealed for &str {}
+	impl Sealed for &&str {}
+	impl Sealed for &OsStr {}
+	impl Sealed for char {}
+	impl Sealed for u8 {}
+	impl<F: FnMut(u8) -> bool> Sealed for F {}
+}
+
+fn find_bytes(haystack: &[u8], needle: &[u8]) -> Option<Range<usize>> {
+	if needle.is_empty() {
+		return Some(0..0);
+	}
+	haystack
+		.windows(needle.len())
+		.position(|window| window == needle)
+		.map(|i| i..i + needle.len())
+}
+
+fn rfind_bytes(haystack: &[u8], needle: &[u8]) -> Option<Range<usize>> {
+	if needle.is_empty() {
+		return Some(haystack.len()..haystack.len());
+	}
+	haystack
+		.windows(needle.len())
+		.rposition(|window| window == needle)
+		.map(|i| i..i + needle.len())
+}
+
+fn prefix_len_bytes(haystack: &[u8], needle: &[u8]) -> Option<usize> {
+	if haystack.starts_with(needle) {
+		Some(needle.len())
+	} else {
+		None
+	}
+}
+
+fn suffix_len_bytes(haystack: &[u8], needle: &[u8]) -> Option<usize> {
+	if haystack.ends_with(needle) {
+		Some(needle.len())
+	} else {
+		None
+	}
+}
+
+macro_rules! impl_bytes_pattern {
+	($($ty:ty => |$s:ident| $bytes:expr;)*) => {
+		$(
+			impl Pattern for $ty {
+				fn find_in(&mut self, haystack: &[u8]) -> Option<Range<usize>> {
+					let $s = self;
+					find_bytes(haystack, $bytes)
+				}
+
+				fn rfind_in(&mut self, haystack: &[u8]) -> Option<Range<usize>> {
+					let $s = self;
+					rfind_bytes(haystack, $bytes)
+				}
+
+				fn prefix_len(&mut self, haystack: &[u8]) -> Option<usize> {
+					let $s = self;
+					prefix_len_bytes(haystack, $bytes)
+				}
+
+				fn suffix_len(&mut self, haystack: &[u8]) -> Option<usize> {
+					let $s = self;
+					suffix_len_bytes(haystack, $bytes)
+				}
+			}
+		)*
+	};
+}
+
+impl_bytes_pattern! {
+	&[u8] => |s| s;
+	&str => |s| s.as_bytes();
+	&&str => |s| s.as_bytes();
+	&OsStr => |s| s.as_bytes();
+	char => |c| c.encode_utf8(&mut [0; 4]).as_bytes();
+}
+
+impl Pattern for u8 {
+	fn find_in(&mut self, haystack: &[u8]) -> Option<Range<usize>> {
+		let b = *self;
+		(move |x| x == b).find_in(haystack)
+	}
+
+	fn rfind_in(&mut self, haystack: &[u8]) -> Option<Range<usize>> {
+		let b = *self;
+		(move |x| x == b).rfind_in(haystack)
+	}
+
+	fn prefix_len(&mut self, haystack: &[u8]) -> Option<usize> {
+		let b = *self;
+		(move |x| x == b).prefix_len(haystack)
+	}
+
+	fn suffix_len(&mut self, haystack: &[u8]) -> Option<usize> {
+		let b = *self;
+		(move |x| x == b).suffix_len(haystack)
+	}
+}
+
+impl<F: FnMut(u8) -> bool> Pattern for F {
+	fn find_in(&mut self, haystack: &[u8]) -> Option<Range<usize>> {
+		haystack.iter().position(|&b| self(b)).map(|i| i..i + 1)
+	}
+
+	fn rfind_in(&mut self, haystack: &[u8]) -> Option<Range<usize>> {
+		haystack.iter().rposition(|&b| self(b)).map(|i| i..i + 1)
+	}
+
+	fn prefix_len(&mut self, haystack: &[u8]) -> Option<usize> {
+		match haystack.first() {
+			Some(&b) if self(b) => Some(1),
+			_ => None,
+		}
+	}
+
+	fn suffix_len(&mut self, haystack: &[u8]) -> Option<usize> {
+		match haystack.last() {
+			Some(&b) if self(b) => Some(1),
+			_ => None,
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn test_byte_needles() {
+		let haystack = b"abcabc";
+		assert_eq!((&b"bc"[..]).find_in(haystack), Some(1..3));
+		assert_eq!((&b"bc"[..]).rfind_in(haystack), Some(4..6));
+		assert_eq!((&b"cb"[..]).find_in(haystack), None);
+		assert_eq!((&b""[..]).find_in(haystack), Some(0..0));
+		assert_eq!((&b""[..]).rfind_in(haystack), Some(6..6));
+		assert_eq!((&b"abcabcd"[..]).find_in(haystack), None);
+		assert_eq!("ab".prefix_len(haystack), Some(2));
+		assert_eq!("ab".suffix_len(haystack), None);
+	}
+
+	#[test]
+	fn test_char_and_byte_patterns() {
+		let haystack = "a\u{e9}b\u{e9}".as_bytes();
+		assert_eq!('\u{e9}'.find_in(haystack), Some(1..3));
+		assert_eq!('\u{e9}'.rfind_in(haystack), Some(4..6));
+		assert_eq!('\u{e9}'.suffix_len(haystack), Some(2));
+		assert_eq!(b'b'.find_in(haystack), Some(3..4));
+		assert_eq!((|b: u8| b >= 0x80).rfind_in(haystack), Some(5..6));
+		assert_eq!((|b: u8| b == b'a').prefix_len(haystack), Some(1));
+		assert_eq!((|b: u8| b == b'a').prefix_len(b""), None);
+	}
+}
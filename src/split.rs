@@ -0,0 +1,431 @@
+//! Iterators over sub-slices of an [`OsStr`], created by its splitting
+//! methods.
+//!
+//! [`OsStr`]: crate::OsStr
+use core::iter::FusedIterator;
+
+use crate::pattern::Pattern;
+use crate::OsStr;
+use crate::OsStrExt;
+
+/// The state shared by the forward and reverse splitting iterators.
+#[derive(Clone, Debug)]
+struct SplitInternal<'a, P> {
+	haystack: &'a [u8],
+	// The part of the haystack that has not been yielded yet
+	start: usize,
+	end: usize,
+	pat: P,
+	// The position of the last empty match, which must not be matched again
+	last_empty_match: Option<usize>,
+	allow_trailing_empty: bool,
+	finished: bool,
+}
+
+impl<'a, P: Pattern> SplitInternal<'a, P> {
+	fn new(haystack: &'a OsStr, pat: P) -> SplitInternal<'a, P> {
+		let haystack = haystack.as_bytes();
+		SplitInternal {
+			haystack,
+			start: 0,
+			end: haystack.len(),
+			pat,
+			last_empty_match: None,
+			allow_trailing_empty: true,
+			finished: false,
+		}
+	}
+
+	fn get_end(&mut self) -> Option<&'a OsStr> {
+		if !self.finished {
+			self.finished = true;
+			if self.allow_trailing_empty || self.end > self.start {
+				return Some(OsStr::from_bytes(&self.haystack[self.start..self.end]));
+			}
+		}
+		None
+	}
+
+	fn next(&mut self) -> Option<&'a OsStr> {
+		if self.finished {
+			return None;
+		}
+
+		let mut position = self.start;
+		loop {
+			let m = self
+				.pat
+				.find_in(&self.haystack[position..self.end])
+				.map(|m| m.start + position..m.end + position);
+			match m {
+				Some(m) if m.is_empty() && self.last_empty_match == Some(m.start) => {
+					if m.start >= self.end {
+						return self.get_end();
+					}
+					position = m.start + 1;
+				}
+				Some(m) => {
+					let elt = &self.haystack[self.start..m.start];
+					self.start = m.end;
+					if m.is_empty() {
+						self.last_empty_match = Some(m.start);
+					}
+					return Some(OsStr::from_bytes(elt));
+				}
+				None => return self.get_end(),
+			}
+		}
+	}
+
+	fn next_back(&mut self) -> Option<&'a OsStr> {
+		if self.finished {
+			return None;
+		}
+
+		let mut position = self.end;
+		loop {
+			let m = self
+				.pat
+				.rfind_in(&self.haystack[self.start..position])
+				.map(|m| m.start + self.start..m.end + self.start);
+			match m {
+				Some(m) if m.is_empty() && self.last_empty_match == Some(m.start) => {
+					if m.start <= self.start {
+						return self.get_end();
+					}
+					position = m.start - 1;
+				}
+				Some(m) => {
+					let elt = &self.haystack[m.end..self.end];
+					self.end = m.start;
+					if m.is_empty() {
+						self.last_empty_match = Some(m.start);
+					}
+					return Some(OsStr::from_bytes(elt));
+				}
+				None => return self.get_end(),
+			}
+		}
+	}
+}
+
+macro_rules! split_iter {
+	($(#[$attr:meta])* $name:ident, $next:ident) => {
+		$(#[$attr])*
+		#[derive(Clone, Debug)]
+		pub struct $name<'a, P>(SplitInternal<'a, P>);
+
+		impl<'a, P: Pattern> Iterator for $name<'a, P> {
+			type Item = &'a OsStr;
+
+			#[inline]
+			fn next(&mut self) -> Option<&'a OsStr> {
+				self.0.$next()
+			}
+		}
+
+		impl<P: Pattern> FusedIterator for $name<'_, P> {}
+	};
+}
+
+split_iter! {
+	/// An iterator over sub-slices of an [`OsStr`], separated by a pattern.
+	///
+	/// This `struct` is created by the [`split`] method on [`OsStr`].
+	///
+	/// [`split`]: OsStr::split
+	Split, next
+}
+
+split_iter! {
+	/// An iterator over sub-slices of an [`OsStr`], separated by a pattern
+	/// and produced in reverse order.
+	///
+	/// This `struct` is created by the [`rsplit`] method on [`OsStr`].
+	///
+	/// [`rsplit`]: OsStr::rsplit
+	RSplit, next_back
+}
+
+split_iter! {
+	/// An iterator over sub-slices of an [`OsStr`], separated by a pattern,
+	/// that skips a trailing empty sub-slice.
+	///
+	/// This `struct` is created by the [`split_terminator`] method on
+	/// [`OsStr`].
+	///
+	/// [`split_terminator`]: OsStr::split_terminator
+	SplitTerminator, next
+}
+
+macro_rules! splitn_iter {
+	($(#[$attr:meta])* $name:ident, $next:ident) => {
+		$(#[$attr])*
+		#[derive(Clone, Debug)]
+		pub struct $name<'a, P> {
+			iter: SplitInternal<'a, P>,
+			// The number of splits remaining
+			count: usize,
+		}
+
+		impl<'a, P: Pattern> Iterator for $name<'a, P> {
+			type Item = &'a OsStr;
+
+			#[inline]
+			fn next(&mut self) -> Option<&'a OsStr> {
+				match self.count {
+					0 => None,
+					1 => {
+						self.count = 0;
+						self.iter.get_end()
+					}
+					_ => {
+						self.count -= 1;
+						self.iter.$next()
+					}
+				}
+			}
+		}
+
+		impl<P: Pattern> FusedIterator for $name<'_, P> {}
+	};
+}
+
+splitn_iter! {
+	/// An iterator over at most `n` sub-slices of an [`OsStr`], separated by
+	/// a pattern.
+	///
+	/// This `struct` is created by the [`splitn`] method on [`OsStr`].
+	///
+	/// [`splitn`]: OsStr::splitn
+	SplitN, next
+}
+
+splitn_iter! {
+	/// An iterator over at most `n` sub-slices of an [`OsStr`], separated by
+	/// a pattern, starting from the end of the string.
+	///
+	/// This `struct` is created by the [`rsplitn`] method on [`OsStr`].
+	///
+	/// [`rsplitn`]: OsStr::rsplitn
+	RSplitN, next_back
+}
+
+impl<'a, P: Pattern> Split<'a, P> {
+	pub(crate) fn new(haystack: &'a OsStr, pat: P) -> Split<'a, P> {
+		Split(SplitInternal::new(haystack, pat))
+	}
+}
+
+impl<'a, P: Pattern> RSplit<'a, P> {
+	pub(crate) fn new(haystack: &'a OsStr, pat: P) -> RSplit<'a, P> {
+		RSplit(SplitInternal::new(haystack, pat))
+	}
+}
+
+impl<'a, P: Pattern> SplitTerminator<'a, P> {
+	pub(crate) fn new(haystack: &'a OsStr, pat: P) -> SplitTerminator<'a, P> {
+		SplitTerminator(SplitInternal {
+			allow_trailing_empty: false,
+			..SplitInternal::new(haystack, pat)
+		})
+	}
+}
+
+impl<'a, P: Pattern> SplitN<'a, P> {
+	pub(crate) fn new(haystack: &'a OsStr, pat: P, count: usize) -> SplitN<'a, P> {
+		SplitN {
+			iter: SplitInternal::new(haystack, pat),
+			count,
+		}
+	}
+}
+
+impl<'a, P: Pattern> RSplitN<'a, P> {
+	pub(crate) fn new(haystack: &'a OsStr, pat: P, count: usize) -> RSplitN<'a, P> {
+		RSplitN {
+			iter: SplitInternal::new(haystack, pat),
+			count,
+		}
+	}
+}
+
+/// An iterator over the non-whitespace sub-slices of an [`OsStr`], separated
+/// by any amount of ASCII whitespace.
+///
+/// This `struct` is created by the [`split_ascii_whitespace`] method on
+/// [`OsStr`].
+///
+/// [`split_ascii_whitespace`]: OsStr::split_ascii_whitespace
+#[derive(Clone, Debug)]
+pub struct SplitAsciiWhitespace<'a> {
+	bytes: &'a [u8],
+}
+
+impl<'a> SplitAsciiWhitespace<'a> {
+	pub(crate) fn new(haystack: &'a OsStr) -> SplitAsciiWhitespace<'a> {
+		SplitAsciiWhitespace {
+			bytes: haystack.as_bytes(),
+		}
+	}
+}
+
+impl<'a> Iterator for SplitAsciiWhitespace<'a> {
+	type Item = &'a OsStr;
+
+	fn next(&mut self) -> Option<&'a OsStr> {
+		let start = self.bytes.iter().position(|b| !b.is_ascii_whitespace())?;
+		let bytes = &self.bytes[start..];
+		let end = bytes
+			.iter()
+			.position(u8::is_ascii_whitespace)
+			.unwrap_or(bytes.len());
+		self.bytes = &bytes[end..];
+		Some(OsStr::from_bytes(&bytes[..end]))
+	}
+}
+
+impl<'a> DoubleEndedIterator for SplitAsciiWhitespace<'a> {
+	fn next_back(&mut self) -> Option<&'a OsStr> {
+		let end = self.bytes.iter().rposition(|b| !b.is_ascii_whitespace())? + 1;
+		let bytes = &self.bytes[..end];
+		let start = bytes
+			.iter()
+			.rposition(u8::is_ascii_whitespace)
+			.map_or(0, |i| i + 1);
+		self.bytes = &bytes[..start];
+		Some(OsStr::from_bytes(&bytes[start..]))
+	}
+}
+
+impl FusedIterator for SplitAsciiWhitespace<'_> {}
+
+/// An iterator over the lines of an [`OsStr`].
+///
+/// This `struct` is created by the [`lines`] method on [`OsStr`].
+///
+/// [`lines`]: OsStr::lines
+#[derive(Clone, Debug)]
+pub struct Lines<'a> {
+	bytes: &'a [u8],
+}
+
+impl<'a> Lines<'a> {
+	pub(crate) fn new(haystack: &'a OsStr) -> Lines<'a> {
+		Lines {
+			bytes: haystack.as_bytes(),
+		}
+	}
+}
+
+/// Removes the line ending from `line`, which is either `\n` or `\r\n`. A
+/// `\r` that is not followed by `\n` is part of the line.
+fn strip_line_ending(line: &[u8]) -> &OsStr {
+	let line = match line.strip_suffix(b"\n") {
+		Some(line) => line.strip_suffix(b"\r").unwrap_or(line),
+		None => line,
+	};
+	OsStr::from_bytes(line)
+}
+
+impl<'a> Iterator for Lines<'a> {
+	type Item = &'a OsStr;
+
+	fn next(&mut self) -> Option<&'a OsStr> {
+		if self.bytes.is_empty() {
+			return None;
+		}
+		let (line, rest) = match self.bytes.iter().position(|&b| b == b'\n') {
+			Some(i) => self.bytes.split_at(i + 1),
+			None => (self.bytes, &[][..]),
+		};
+		self.bytes = rest;
+		Some(strip_line_ending(line))
+	}
+}
+
+impl<'a> DoubleEndedIterator for Lines<'a> {
+	fn next_back(&mut self) -> Option<&'a OsStr> {
+		let (&last, bytes) = self.bytes.split_last()?;
+		let search = if last == b'\n' { bytes } else { self.bytes };
+		let (rest, line) = match search.iter().rposition(|&b| b == b'\n') {
+			Some(i) => self.bytes.split_at(i + 1),
+			None => (&[][..], self.bytes),
+		};
+		self.bytes = rest;
+		Some(strip_line_ending(line))
+	}
+}
+
+impl FusedIterator for Lines<'_> {}
+
+#[cfg(test)]
+mod tests {
+	use crate::OsStr;
+
+	fn check<'a, I: Iterator<Item = &'a OsStr>>(iter: I, expected: &[&str]) {
+		let mut iter = iter;
+		for &e in expected {
+			assert_eq!(iter.next(), Some(OsStr::new(e)));
+		}
+		assert_eq!(iter.next(), None);
+	}
+
+	#[test]
+	fn test_split() {
+		let s = OsStr::new("a,b,,c,");
+		check(s.split(','), &["a", "b", "", "c", ""]);
+		check(s.rsplit(','), &["", "c", "", "b", "a"]);
+		check(s.split_terminator(','), &["a", "b", "", "c"]);
+		check(OsStr::new("").split(','), &[""]);
+		check(OsStr::new("").split_terminator(','), &[]);
+		check(OsStr::new("aXXbXXXc").split("XX"), &["a", "b", "Xc"]);
+		check(OsStr::new("aXXbXXXc").rsplit("XX"), &["c", "bX", "a"]);
+	}
+
+	#[test]
+	fn test_split_empty_pattern() {
+		check(OsStr::new("ab").split(""), &["", "a", "b", ""]);
+		check(OsStr::new("ab").rsplit(""), &["", "b", "a", ""]);
+		check(OsStr::new("").split(""), &["", ""]);
+		check(OsStr::new("ab").splitn(2, ""), &["", "ab"]);
+	}
+
+	#[test]
+	fn test_splitn() {
+		let s = OsStr::new("a:b:c");
+		check(s.splitn(0, ':'), &[]);
+		check(s.splitn(1, ':'), &["a:b:c"]);
+		check(s.splitn(2, ':'), &["a", "b:c"]);
+		check(s.splitn(5, ':'), &["a", "b", "c"]);
+		check(s.rsplitn(2, ':'), &["c", "a:b"]);
+	}
+
+	#[test]
+	fn test_split_ascii_whitespace() {
+		let s = OsStr::new(" \tfoo  bar\n\x0Cbaz \r\n");
+		check(s.split_ascii_whitespace(), &["foo", "bar", "baz"]);
+		check(s.split_ascii_whitespace().rev(), &["baz", "bar", "foo"]);
+		check(OsStr::new(" \n").split_ascii_whitespace(), &[]);
+	}
+
+	#[test]
+	fn test_lines() {
+		let s = OsStr::new("a\r\n\nb\nc\r\n");
+		check(s.lines(), &["a", "", "b", "c"]);
+		check(s.lines().rev(), &["c", "b", "", "a"]);
+		check(OsStr::new("a\n\n").lines().rev(), &["", "a"]);
+		check(OsStr::new("a\nb").lines(), &["a", "b"]);
+		check(OsStr::new("").lines(), &[]);
+		check(OsStr::new("\n").lines().rev(), &[""]);
+	}
+
+	#[test]
+	fn test_lines_bare_cr() {
+		check(OsStr::new("a\r").lines(), &["a\r"]);
+		check(OsStr::new("a\r").lines().rev(), &["a\r"]);
+		check(OsStr::new("a\rb\r\n\r").lines(), &["a\rb", "\r"]);
+		check(OsStr::new("a\rb\r\n\r").lines().rev(), &["\r", "a\rb"]);
+		check(OsStr::new("\r\n").lines(), &[""]);
+	}
+}
@@ -0,0 +1,280 @@
+use core::cmp;
+use core::fmt;
+use core::iter::FusedIterator;
+
+use crate::lossy::{decode_unit, decode_unit_back, Utf8LossyChunk, Utf8LossyChunksIter};
+use crate::utf8_char_width;
+
+/// A piece of decoded text returned by [`Utf8Decoder::decode`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Utf8Piece<'a> {
+	/// A sequence of valid UTF-8.
+	Str(&'a str),
+	/// An invalid sequence, which stands for a single
+	/// [`U+FFFD REPLACEMENT CHARACTER`][U+FFFD].
+	///
+	/// [U+FFFD]: core::char::REPLACEMENT_CHARACTER
+	Replacement,
+}
+
+impl<'a> Utf8Piece<'a> {
+	/// Returns the text of this piece, with [`Replacement`] turned into
+	/// `"\u{FFFD}"`.
+	///
+	/// [`Replacement`]: Utf8Piece::Replacement
+	pub fn as_str(&self) -> &'a str {
+		match *self {
+			Utf8Piece::Str(s) => s,
+			Utf8Piece::Replacement => "\u{FFFD}",
+		}
+	}
+}
+
+/// A stateful UTF-8 decoder for byte streams that arrive in pieces.
+///
+/// Each call to [`decode`] takes the next chunk of input and returns the
+/// valid text and replacement markers it contains, like a lossy conversion
+/// would. A multi-byte character that is split across two chunks is held
+/// back at the end of the first one and completed by the next call, so the
+/// output does not depend on how the stream is divided. Once the stream has
+/// ended, [`finish`] reports a sequence that was left incomplete.
+///
+/// The decoder never allocates; it only buffers the at most 3 bytes of an
+/// incomplete character.
+///
+/// [`decode`]: Utf8Decoder::decode
+/// [`finish`]: Utf8Decoder::finish
+///
+/// # Examples
+///
+/// ```
+/// use embedded_ffi::{Utf8Decoder, Utf8Piece};
+///
+/// let mut decoder = Utf8Decoder::new();
+/// let mut line = String::new();
+///
+/// // "é" is split across the two reads
+/// for read in [&b"caf\xC3"[..], b"\xA9 \xFF!"] {
+///     for piece in decoder.decode(read) {
+///         line.push_str(piece.as_str());
+///     }
+/// }
+/// assert_eq!(decoder.finish(), None);
+/// assert_eq!(line, "café \u{FFFD}!");
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct Utf8Decoder {
+	// The valid prefix of a character that was cut off by the end of the
+	// previous chunk
+	pending: [u8; 4],
+	pending_len: usize,
+	// A character that was completed from `pending`
+	stitched: [u8; 4],
+}
+
+impl Utf8Decoder {
+	/// Creates a new decoder at the start of a stream.
+	pub const fn new() -> Utf8Decoder {
+		Utf8Decoder {
+			pending: [0; 4],
+			pending_len: 0,
+			stitched: [0; 4],
+		}
+	}
+
+	/// Decodes the next chunk of the stream.
+	///
+	/// An incomplete character at the end of `input` is not returned, but
+	/// kept in the decoder until the next call to [`decode`] or [`finish`].
+	///
+	/// [`decode`]: Utf8Decoder::decode
+	/// [`finish`]: Utf8Decoder::finish
+	pub fn decode<'a>(&'a mut self, input: &'a [u8]) -> Utf8Decode<'a> {
+		let mut input = input;
+		let mut stitched_len = None;
+		let mut replacement = false;
+
+		if self.pending_len > 0 {
+			let len = self.pending_len;
+			let width = utf8_char_width(self.pending[0]);
+			let taken = cmp::min(width - len, input.len());
+			let mut buf = self.pending;
+			buf[len..len + taken].copy_from_slice(&input[..taken]);
+			self.pending_len = 0;
+
+			// The pending bytes are a valid prefix, so the unit that is
+			// decoded here always covers all of them
+			match decode_unit(&buf[..len + taken]) {
+				Ok(unit) => {
+					self.stitched = buf;
+					stitched_len = Some(unit);
+					input = &input[unit - len..];
+				}
+				Err(unit) if unit == len + taken && unit < width => {
+					self.pending = buf;
+					self.pending_len = unit;
+					input = &[];
+				}
+				Err(unit) => {
+					replacement = true;
+					input = &input[unit - len..];
+				}
+			}
+		}
+
+		if let Some(Err(unit)) = input.last().map(|_| decode_unit_back(input)) {
+			let start = input.len() - unit;
+			if utf8_char_width(input[start]) > unit {
+				self.pending[..unit].copy_from_slice(&input[start..]);
+				self.pending_len = unit;
+				input = &input[..start];
+			}
+		}
+
+		let first = match stitched_len {
+			Some(len) => Some(Utf8Piece::Str(unsafe {
+				core::str::from_utf8_unchecked(&self.stitched[..len])
+			})),
+			None if replacement => Some(Utf8Piece::Replacement),
+			None => None,
+		};
+		Utf8Decode {
+			first,
+			chunks: Utf8LossyChunksIter { source: input },
+			replacement: false,
+		}
+	}
+
+	/// Ends the stream, returning a [`Replacement`] if it stopped in the
+	/// middle of a character.
+	///
+	/// The decoder is reset and can be used for a new stream afterwards.
+	///
+	/// [`Replacement`]: Utf8Piece::Replacement
+	///
+	/// # Examples
+	///
+	/// ```
+	/// use embedded_ffi::{Utf8Decoder, Utf8Piece};
+	///
+	/// let mut decoder = Utf8Decoder::new();
+	/// assert_eq!(decoder.decode(b"ab\xE2\x82").next(), Some(Utf8Piece::Str("ab")));
+	/// assert_eq!(decoder.finish(), Some(Utf8Piece::Replacement));
+	/// assert_eq!(decoder.finish(), None);
+	/// ```
+	pub fn finish(&mut self) -> Option<Utf8Piece<'static>> {
+		if self.pending_len > 0 {
+			self.pending_len = 0;
+			Some(Utf8Piece::Replacement)
+		} else {
+			None
+		}
+	}
+}
+
+/// An iterator over the pieces of text decoded from a chunk of input.
+///
+/// This `struct` is created by the [`decode`] method on [`Utf8Decoder`].
+///
+/// [`decode`]: Utf8Decoder::decode
+#[derive(Clone)]
+pub struct Utf8Decode<'a> {
+	first: Option<Utf8Piece<'a>>,
+	chunks: Utf8LossyChunksIter<'a>,
+	// Whether the last chunk ended in an invalid sequence that has not been
+	// returned yet
+	replacement: bool,
+}
+
+impl fmt::Debug for Utf8Decode<'_> {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		f.debug_struct("Utf8Decode").finish_non_exhaustive()
+	}
+}
+
+impl<'a> Iterator for Utf8Decode<'a> {
+	type Item = Utf8Piece<'a>;
+
+	fn next(&mut self) -> Option<Utf8Piece<'a>> {
+		if let Some(piece) = self.first.take() {
+			return Some(piece);
+		}
+		if self.replacement {
+			self.replacement = false;
+			return Some(Utf8Piece::Replacement);
+		}
+
+		let Utf8LossyChunk { valid, broken } = self.chunks.next()?;
+		if valid.is_empty() {
+			Some(Utf8Piece::Replacement)
+		} else {
+			self.replacement = !broken.is_empty();
+			Some(Utf8Piece::Str(valid))
+		}
+	}
+}
+
+impl FusedIterator for Utf8Decode<'_> {}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::{ArrayOsString, OsStr};
+
+	fn decode_in_pieces(input: &[u8], split: &[usize]) -> ArrayOsString<64> {
+		let mut decoder = Utf8Decoder::new();
+		let mut output = ArrayOsString::new();
+		let mut start = 0;
+		for &end in split.iter().chain(Some(&input.len())) {
+			for piece in decoder.decode(&input[start..end]) {
+				output.push(piece.as_str());
+			}
+			start = end;
+		}
+		if let Some(piece) = decoder.finish() {
+			output.push(piece.as_str());
+		}
+		output
+	}
+
+	#[test]
+	fn test_decode_split_anywhere() {
+		let input = b"a\xC3\xA9\xF0\x9F\x98\x80\xFF\xE2\x82b\xED\xA0\x80\xF0\x9F";
+		let expected = "a\u{e9}\u{1f600}\u{FFFD}\u{FFFD}b\u{FFFD}\u{FFFD}\u{FFFD}\u{FFFD}";
+		assert_eq!(decode_in_pieces(input, &[]), expected);
+		for i in 0..=input.len() {
+			assert_eq!(decode_in_pieces(input, &[i]), expected, "split at {}", i);
+			for j in i..=input.len() {
+				assert_eq!(decode_in_pieces(input, &[i, j]), expected);
+			}
+		}
+	}
+
+	#[test]
+	fn test_decode_byte_by_byte() {
+		let input = "\u{10ffff}x\u{800}".as_bytes();
+		let splits = [1, 2, 3, 4, 5, 6, 7];
+		assert_eq!(
+			decode_in_pieces(input, &splits),
+			OsStr::from_bytes_const(input)
+		);
+	}
+
+	#[test]
+	fn test_decode_pending() {
+		let mut decoder = Utf8Decoder::new();
+		assert_eq!(decoder.decode(b"\xF0").next(), None);
+		assert_eq!(decoder.decode(b"").next(), None);
+		assert_eq!(decoder.decode(b"\x9F\x98").next(), None);
+		let mut pieces = decoder.decode(b"\x80!");
+		assert_eq!(pieces.next(), Some(Utf8Piece::Str("\u{1f600}")));
+		assert_eq!(pieces.next(), Some(Utf8Piece::Str("!")));
+		assert_eq!(pieces.next(), None);
+
+		assert_eq!(decoder.decode(b"\xE2").next(), None);
+		let mut pieces = decoder.decode(b"x");
+		assert_eq!(pieces.next(), Some(Utf8Piece::Replacement));
+		assert_eq!(pieces.next(), Some(Utf8Piece::Str("x")));
+		assert_eq!(decoder.finish(), None);
+	}
+}
@@ -0,0 +1,1157 @@
+//! UTF-16 strings, as used by UEFI and by `wchar_t` APIs on targets where
+//! `wchar_t` is 16 bits wide.
+//!
+//! [`WStr`] is a borrowed slice of UTF-16 code units that may contain
+//! unpaired surrogates, [`WString`] is its owned counterpart and [`WCStr`]
+//! is a nul-terminated string as handed out by C (e.g. `CHAR16*`).
+//!
+//! Conversions to and from [`OsStr`] are lossless when the input is valid
+//! UTF-16 or UTF-8 respectively. The lossy variants replace every invalid
+//! sequence with a single [`U+FFFD REPLACEMENT CHARACTER`][U+FFFD].
+//!
+//! # WTF-8
+//!
+//! UTF-16 from sources such as FAT long file names may contain unpaired
+//! surrogates, which UTF-8 cannot represent. [WTF-8] extends UTF-8 by
+//! encoding each unpaired surrogate like any other code point, as three bytes
+//! starting with `0xED`. [`WStr::to_os_string_wtf8`] and
+//! [`OsStr::decode_wtf8`] convert between the two losslessly, so such names
+//! can be kept in an [`OsString`] and written back unchanged.
+//!
+//! The encoded surrogates are not valid UTF-8: [`OsStr::to_str`] returns
+//! `None` for such a string, and its lossy conversions replace each of their
+//! bytes with [`U+FFFD`][U+FFFD], like `String::from_utf8_lossy` does. To
+//! show each surrogate as a single [`U+FFFD`][U+FFFD], like the [`WStr`] the
+//! name came from, decode it with [`OsStr::decode_wtf8`] first.
+//!
+//! [WTF-8]: https://simonsapin.github.io/wtf-8/
+//! [`OsStr::decode_wtf8`]: crate::OsStr::decode_wtf8
+//! [`OsStr::to_str`]: crate::OsStr::to_str
+//! [`OsString`]: crate::OsString
+//! [`OsStr`]: crate::OsStr
+//! [U+FFFD]: core::char::REPLACEMENT_CHARACTER
+#[cfg(feature = "alloc")]
+use alloc::borrow::{Cow, ToOwned};
+#[cfg(feature = "alloc")]
+use alloc::vec::Vec;
+#[cfg(feature = "alloc")]
+use core::borrow::Borrow;
+use core::char::{self, DecodeUtf16, DecodeUtf16Error};
+use core::fmt::{self, Write};
+use core::hash::Hash;
+use core::iter::{Copied, FusedIterator};
+#[cfg(feature = "alloc")]
+use core::ops;
+use core::slice;
+#[cfg(feature = "alloc")]
+use core::str;
+
+use crate::lossy::{decode_unit, fmt_padded};
+#[cfg(feature = "alloc")]
+use crate::lossy::{Utf8Lossy, Utf8LossyChunk};
+use crate::CapacityError;
+#[cfg(feature = "alloc")]
+use crate::{OsStr, OsStrExt, OsString};
+
+/// A borrowed UTF-16 string, which may contain unpaired surrogates.
+///
+/// # Examples
+///
+/// ```
+/// use embedded_ffi::WStr;
+///
+/// let units = [0x48, 0x69, 0xD800];
+/// let s = WStr::from_slice(&units);
+/// assert_eq!(s.len(), 3);
+/// assert_eq!(s.display().to_string(), "Hi\u{FFFD}");
+/// ```
+#[derive(PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[repr(transparent)]
+pub struct WStr {
+	inner: [u16],
+}
+
+impl WStr {
+	/// Wraps a slice of UTF-16 code units as a `WStr`.
+	pub fn from_slice(slice: &[u16]) -> &WStr {
+		unsafe { &*(slice as *const [u16] as *const WStr) }
+	}
+
+	/// Wraps a mutable slice of UTF-16 code units as a `WStr`.
+	pub fn from_slice_mut(slice: &mut [u16]) -> &mut WStr {
+		unsafe { &mut *(slice as *mut [u16] as *mut WStr) }
+	}
+
+	/// Returns the code units of this string.
+	pub fn as_slice(&self) -> &[u16] {
+		&self.inner
+	}
+
+	/// Returns the code units of this string as a mutable slice.
+	pub fn as_mut_slice(&mut self) -> &mut [u16] {
+		&mut self.inner
+	}
+
+	/// Returns the length of this string in code units.
+	pub fn len(&self) -> usize {
+		self.inner.len()
+	}
+
+	/// Returns `true` if this string has a length of zero.
+	pub fn is_empty(&self) -> bool {
+		self.inner.is_empty()
+	}
+
+	/// Returns an iterator over the `char`s of this string.
+	///
+	/// Unpaired surrogates are returned as errors.
+	///
+	/// # Examples
+	///
+	/// ```
+	/// use embedded_ffi::WStr;
+	///
+	/// let units = [0xD83D, 0xDE00, 0xDC00];
+	/// let mut chars = WStr::from_slice(&units).chars();
+	/// assert_eq!(chars.next(), Some(Ok('\u{1F600}')));
+	/// assert_eq!(chars.next().unwrap().unwrap_err().unpaired_surrogate(), 0xDC00);
+	/// assert_eq!(chars.next(), None);
+	/// ```
+	pub fn chars(&self) -> DecodeUtf16<Copied<slice::Iter<'_, u16>>> {
+		char::decode_utf16(self.inner.iter().copied())
+	}
+
+	/// Returns an object that implements [`Display`] for printing this string,
+	/// replacing each unpaired surrogate with
+	/// [`U+FFFD REPLACEMENT CHARACTER`][U+FFFD].
+	///
+	/// [`Display`]: fmt::Display
+	/// [U+FFFD]: core::char::REPLACEMENT_CHARACTER
+	pub fn display(&self) -> Display<'_> {
+		Display { inner: self }
+	}
+
+	/// Converts this string to an [`OsString`] of UTF-8 bytes.
+	///
+	/// # Errors
+	///
+	/// Returns the first unpaired surrogate as an error, since it cannot be
+	/// represented in UTF-8.
+	///
+	/// # Examples
+	///
+	/// ```
+	/// use embedded_ffi::WStr;
+	///
+	/// let units = [0x62, 0x6F, 0x6F, 0x74];
+	/// assert_eq!(WStr::from_slice(&units).to_os_string().unwrap(), "boot");
+	/// assert!(WStr::from_slice(&[0xD800]).to_os_string().is_err());
+	/// ```
+	#[cfg(feature = "alloc")]
+	pub fn to_os_string(&self) -> Result<OsString, DecodeUtf16Error> {
+		let mut os_string = OsString::with_capacity(self.len());
+		for c in self.chars() {
+			os_string.push(c?.encode_utf8(&mut [0; 4]));
+		}
+		Ok(os_string)
+	}
+
+	/// Converts this string to an [`OsString`] of UTF-8 bytes, replacing each
+	/// unpaired surrogate with [`U+FFFD REPLACEMENT CHARACTER`][U+FFFD].
+	///
+	/// [U+FFFD]: core::char::REPLACEMENT_CHARACTER
+	#[cfg(feature = "alloc")]
+	pub fn to_os_string_lossy(&self) -> OsString {
+		let mut os_string = OsString::with_capacity(self.len());
+		for c in self.chars() {
+			let c = c.unwrap_or(char::REPLACEMENT_CHARACTER);
+			os_string.push(c.encode_utf8(&mut [0; 4]));
+		}
+		os_string
+	}
+
+	/// Converts this string to an [`OsString`] of WTF-8 bytes, encoding each
+	/// unpaired surrogate as a three-byte sequence.
+	///
+	/// This never fails, and [`OsStr::decode_wtf8`] turns the result back
+	/// into the original code units. The result is plain UTF-8 if this
+	/// string is valid UTF-16.
+	///
+	/// [`OsStr::decode_wtf8`]: crate::OsStr::decode_wtf8
+	///
+	/// # Examples
+	///
+	/// ```
+	/// use embedded_ffi::{OsStrExt, WStr};
+	///
+	/// let units = [0x61, 0xD800];
+	/// let name = WStr::from_slice(&units).to_os_string_wtf8();
+	/// assert_eq!(name.as_bytes(), b"a\xED\xA0\x80");
+	/// assert_eq!(name.to_str(), None);
+	/// assert!(name.decode_wtf8().map(Result::unwrap).eq(units.iter().copied()));
+	/// ```
+	#[cfg(feature = "alloc")]
+	pub fn to_os_string_wtf8(&self) -> OsString {
+		let mut os_string = OsString::with_capacity(self.len());
+		for c in self.chars() {
+			os_string.push(OsStr::from_bytes(encode_wtf8(c, &mut [0; 4])));
+		}
+		os_string
+	}
+
+	/// Writes this string as WTF-8 into `buf`, like
+	/// [`to_os_string_wtf8`], and returns the number of bytes written.
+	///
+	/// [`to_os_string_wtf8`]: WStr::to_os_string_wtf8
+	///
+	/// # Errors
+	///
+	/// Returns [`CapacityError`] if the result does not fit into `buf`. The
+	/// contents of `buf` are unspecified in that case.
+	pub fn encode_wtf8_into(&self, buf: &mut [u8]) -> Result<usize, CapacityError> {
+		let mut len = 0;
+		let mut encoded = [0; 4];
+		for c in self.chars() {
+			let bytes = encode_wtf8(c, &mut encoded);
+			buf.get_mut(len..len + bytes.len())
+				.ok_or(CapacityError(()))?
+				.copy_from_slice(bytes);
+			len += bytes.len();
+		}
+		Ok(len)
+	}
+
+	/// Copies this string into an owned [`WString`].
+	#[cfg(feature = "alloc")]
+	pub fn to_wstring(&self) -> WString {
+		WString {
+			inner: self.inner.to_vec(),
+		}
+	}
+}
+
+impl fmt::Debug for WStr {
+	/// Formats the string like a `str`, with unpaired surrogates written as
+	/// `\u{DXXX}` escapes.
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		f.write_char('"')?;
+		for c in self.chars() {
+			match c {
+				Ok('\'') => f.write_char('\'')?,
+				Ok(c) => {
+					for c in c.escape_debug() {
+						f.write_char(c)?;
+					}
+				}
+				Err(err) => write!(f, "\\u{{{:X}}}", err.unpaired_surrogate())?,
+			}
+		}
+		f.write_char('"')
+	}
+}
+
+impl AsRef<WStr> for WStr {
+	fn as_ref(&self) -> &WStr {
+		self
+	}
+}
+
+impl AsRef<[u16]> for WStr {
+	fn as_ref(&self) -> &[u16] {
+		&self.inner
+	}
+}
+
+impl<'a> From<&'a [u16]> for &'a WStr {
+	fn from(slice: &'a [u16]) -> &'a WStr {
+		WStr::from_slice(slice)
+	}
+}
+
+#[cfg(feature = "alloc")]
+impl ToOwned for WStr {
+	type Owned = WString;
+	fn to_owned(&self) -> WString {
+		self.to_wstring()
+	}
+}
+
+/// Helper struct for safely printing a [`WStr`] with [`format!`] and `{}`.
+///
+/// This `struct` is created by the [`display`] method on [`WStr`].
+///
+/// [`format!`]: alloc::format
+/// [`display`]: WStr::display
+pub struct Display<'a> {
+	inner: &'a WStr,
+}
+
+impl fmt::Debug for Display<'_> {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		fmt::Debug::fmt(self.inner, f)
+	}
+}
+
+impl fmt::Display for Display<'_> {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		let chars = self
+			.inner
+			.chars()
+			.map(|c| c.unwrap_or(char::REPLACEMENT_CHARACTER));
+		if f.width().is_some() || f.precision().is_some() {
+			return fmt_padded(f, chars.clone().count(), |f, len| {
+				for c in chars.take(len) {
+					f.write_char(c)?;
+				}
+				Ok(())
+			});
+		}
+		for c in chars {
+			f.write_char(c)?;
+		}
+		Ok(())
+	}
+}
+
+/// An owned, growable UTF-16 string, which may contain unpaired surrogates.
+///
+/// `WString` is to [`WStr`] as [`OsString`] is to [`OsStr`].
+///
+/// [`OsStr`]: crate::OsStr
+///
+/// # Examples
+///
+/// ```
+/// use embedded_ffi::{OsStr, WString};
+///
+/// let mut s = WString::from_os_str(OsStr::new("EFI")).unwrap();
+/// s.push_slice(&[0x5C]);
+/// assert_eq!(s.as_slice(), [0x45, 0x46, 0x49, 0x5C]);
+/// assert_eq!(s.to_os_string().unwrap(), "EFI\\");
+/// ```
+#[cfg(feature = "alloc")]
+#[derive(Clone, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct WString {
+	inner: Vec<u16>,
+}
+
+#[cfg(feature = "alloc")]
+impl WString {
+	/// Constructs a new, empty `WString`.
+	pub fn new() -> WString {
+		WString { inner: Vec::new() }
+	}
+
+	/// Wraps a vector of UTF-16 code units as a `WString`.
+	pub fn from_vec(vec: Vec<u16>) -> WString {
+		WString { inner: vec }
+	}
+
+	/// Converts this `WString` into its vector of code units.
+	pub fn into_vec(self) -> Vec<u16> {
+		self.inner
+	}
+
+	/// Encodes an [`OsStr`] as UTF-16.
+	///
+	/// # Errors
+	///
+	/// Returns an error if `s` is not valid UTF-8.
+	pub fn from_os_str<S: AsRef<OsStr> + ?Sized>(s: &S) -> Result<WString, str::Utf8Error> {
+		let s = str::from_utf8(s.as_ref().as_bytes())?;
+		Ok(WString::from(s))
+	}
+
+	/// Encodes an [`OsStr`] as UTF-16, replacing each invalid UTF-8 sequence
+	/// with [`U+FFFD REPLACEMENT CHARACTER`][U+FFFD].
+	///
+	/// [U+FFFD]: core::char::REPLACEMENT_CHARACTER
+	///
+	/// # Examples
+	///
+	/// ```
+	/// use embedded_ffi::{OsStr, OsStrExt, WString};
+	///
+	/// let s = WString::from_os_str_lossy(OsStr::from_bytes(b"a\xFFb"));
+	/// assert_eq!(s.as_slice(), [0x61, 0xFFFD, 0x62]);
+	/// ```
+	pub fn from_os_str_lossy<S: AsRef<OsStr> + ?Sized>(s: &S) -> WString {
+		let bytes = s.as_ref().as_bytes();
+		let mut inner = Vec::with_capacity(bytes.len());
+		for Utf8LossyChunk { valid, broken } in Utf8Lossy::from_bytes(bytes).chunks() {
+			inner.extend(valid.encode_utf16());
+			if !broken.is_empty() {
+				inner.push(char::REPLACEMENT_CHARACTER as u16);
+			}
+		}
+		WString { inner }
+	}
+
+	/// Decodes a WTF-8 [`OsStr`], such as one created by
+	/// [`WStr::to_os_string_wtf8`], back into UTF-16.
+	///
+	/// # Errors
+	///
+	/// Returns an error if `s` is not well-formed WTF-8.
+	pub fn from_os_str_wtf8<S: AsRef<OsStr> + ?Sized>(s: &S) -> Result<WString, Wtf8Error> {
+		let s = s.as_ref();
+		let mut inner = Vec::with_capacity(s.len());
+		for unit in s.decode_wtf8() {
+			inner.push(unit?);
+		}
+		Ok(WString { inner })
+	}
+
+	/// Converts to a [`WStr`] slice.
+	pub fn as_wstr(&self) -> &WStr {
+		self
+	}
+
+	/// Appends code units to the end of this string.
+	pub fn push_slice(&mut self, units: &[u16]) {
+		self.inner.extend_from_slice(units)
+	}
+
+	/// Appends the UTF-16 encoding of a `str` to the end of this string.
+	pub fn push_str(&mut self, s: &str) {
+		self.inner.extend(s.encode_utf16())
+	}
+}
+
+#[cfg(feature = "alloc")]
+impl ops::Deref for WString {
+	type Target = WStr;
+
+	#[inline]
+	fn deref(&self) -> &WStr {
+		WStr::from_slice(&self.inner)
+	}
+}
+
+#[cfg(feature = "alloc")]
+impl ops::DerefMut for WString {
+	#[inline]
+	fn deref_mut(&mut self) -> &mut WStr {
+		WStr::from_slice_mut(&mut self.inner)
+	}
+}
+
+#[cfg(feature = "alloc")]
+impl fmt::Debug for WString {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		fmt::Debug::fmt(&**self, f)
+	}
+}
+
+#[cfg(feature = "alloc")]
+impl From<&str> for WString {
+	fn from(s: &str) -> WString {
+		let mut w = WString::new();
+		w.push_str(s);
+		w
+	}
+}
+
+#[cfg(feature = "alloc")]
+impl From<Vec<u16>> for WString {
+	fn from(vec: Vec<u16>) -> WString {
+		WString::from_vec(vec)
+	}
+}
+
+#[cfg(feature = "alloc")]
+impl From<&WStr> for WString {
+	fn from(s: &WStr) -> WString {
+		s.to_wstring()
+	}
+}
+
+#[cfg(feature = "alloc")]
+impl<'a> From<WString> for Cow<'a, WStr> {
+	fn from(s: WString) -> Cow<'a, WStr> {
+		Cow::Owned(s)
+	}
+}
+
+#[cfg(feature = "alloc")]
+impl Borrow<WStr> for WString {
+	fn borrow(&self) -> &WStr {
+		self
+	}
+}
+
+#[cfg(feature = "alloc")]
+impl AsRef<WStr> for WString {
+	fn as_ref(&self) -> &WStr {
+		self
+	}
+}
+
+/// Encodes a character or an unpaired surrogate as WTF-8.
+fn encode_wtf8(c: Result<char, DecodeUtf16Error>, buf: &mut [u8; 4]) -> &[u8] {
+	match c {
+		Ok(c) => c.encode_utf8(buf).as_bytes(),
+		Err(err) => {
+			let unit = err.unpaired_surrogate();
+			buf[0] = 0xED;
+			buf[1] = 0x80 | (unit >> 6 & 0x3F) as u8;
+			buf[2] = 0x80 | (unit & 0x3F) as u8;
+			&buf[..3]
+		}
+	}
+}
+
+/// Decodes a surrogate code point that is encoded as WTF-8 at the start of
+/// `bytes`.
+fn decode_surrogate(bytes: &[u8]) -> Option<u16> {
+	match *bytes {
+		[0xED, b1 @ 0xA0..=0xBF, b2 @ 0x80..=0xBF, ..] => {
+			Some(0xD000 | u16::from(b1 & 0x3F) << 6 | u16::from(b2 & 0x3F))
+		}
+		_ => None,
+	}
+}
+
+/// An error returned when an [`OsStr`] is not well-formed WTF-8.
+///
+/// Besides invalid UTF-8, this includes a lead surrogate followed by a trail
+/// surrogate, which WTF-8 requires to be encoded as a single four-byte
+/// character instead.
+///
+/// [`OsStr`]: crate::OsStr
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Wtf8Error {
+	valid_up_to: usize,
+}
+
+impl Wtf8Error {
+	/// Returns the index in the given string up to which well-formed WTF-8
+	/// was verified.
+	pub fn valid_up_to(&self) -> usize {
+		self.valid_up_to
+	}
+}
+
+impl fmt::Display for Wtf8Error {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		write!(f, "invalid WTF-8 sequence from index {}", self.valid_up_to)
+	}
+}
+
+/// An iterator over the UTF-16 code units of a WTF-8 [`OsStr`].
+///
+/// This `struct` is created by the [`decode_wtf8`] method on [`OsStr`]. It
+/// returns a single error and then stops if the input is not well-formed.
+///
+/// [`OsStr`]: crate::OsStr
+/// [`decode_wtf8`]: crate::OsStr::decode_wtf8
+#[derive(Clone, Debug)]
+pub struct DecodeWtf8<'a> {
+	bytes: &'a [u8],
+	pos: usize,
+	// The trail surrogate of the last character, if it needed two units
+	trail: Option<u16>,
+	// Whether the last unit was a lead surrogate encoded on its own
+	lead: bool,
+}
+
+impl<'a> DecodeWtf8<'a> {
+	pub(crate) fn new(bytes: &'a [u8]) -> DecodeWtf8<'a> {
+		DecodeWtf8 {
+			bytes,
+			pos: 0,
+			trail: None,
+			lead: false,
+		}
+	}
+}
+
+impl Iterator for DecodeWtf8<'_> {
+	type Item = Result<u16, Wtf8Error>;
+
+	fn next(&mut self) -> Option<Result<u16, Wtf8Error>> {
+		if let Some(unit) = self.trail.take() {
+			return Some(Ok(unit));
+		}
+		let rest = &self.bytes[self.pos..];
+		if rest.is_empty() {
+			return None;
+		}
+
+		let (unit, len) = match decode_unit(rest) {
+			Ok(len) => {
+				let s = unsafe { core::str::from_utf8_unchecked(&rest[..len]) };
+				let c = s.chars().next().unwrap();
+				let mut units = [0; 2];
+				let units = c.encode_utf16(&mut units);
+				if units.len() == 2 {
+					self.trail = Some(units[1]);
+				}
+				self.lead = false;
+				(units[0], len)
+			}
+			Err(_) => match decode_surrogate(rest) {
+				Some(unit) if !(self.lead && unit >= 0xDC00) => {
+					self.lead = unit < 0xDC00;
+					(unit, 3)
+				}
+				_ => {
+					let err = Wtf8Error {
+						valid_up_to: self.pos,
+					};
+					self.pos = self.bytes.len();
+					return Some(Err(err));
+				}
+			},
+		};
+		self.pos += len;
+		Some(Ok(unit))
+	}
+}
+
+impl FusedIterator for DecodeWtf8<'_> {}
+
+/// An error returned when a slice of code units is not a valid
+/// nul-terminated string.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum FromSliceWithNulError {
+	/// The slice contains a nul before its end, at the given position.
+	InteriorNul(usize),
+	/// The slice does not end with a nul.
+	NotNulTerminated,
+}
+
+impl fmt::Display for FromSliceWithNulError {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		match self {
+			FromSliceWithNulError::InteriorNul(pos) => {
+				write!(
+					f,
+					"data provided contains an interior nul at position {}",
+					pos
+				)
+			}
+			FromSliceWithNulError::NotNulTerminated => {
+				f.write_str("data provided is not nul terminated")
+			}
+		}
+	}
+}
+
+/// The C `wchar_t` type of the target, as an unsigned code unit.
+///
+/// `wchar_t` is 16 bits wide on Windows and UEFI, and 32 bits wide on most
+/// other targets, including Linux and newlib.
+#[allow(non_camel_case_types)]
+#[cfg(any(windows, target_os = "uefi"))]
+pub type c_wchar = u16;
+/// The C `wchar_t` type of the target, as an unsigned code unit.
+///
+/// `wchar_t` is 16 bits wide on Windows and UEFI, and 32 bits wide on most
+/// other targets, including Linux and newlib.
+#[allow(non_camel_case_types)]
+#[cfg(not(any(windows, target_os = "uefi")))]
+pub type c_wchar = u32;
+
+/// A code unit of a wide string: `u16` for UTF-16 or `u32` for UTF-32.
+///
+/// This trait is sealed and cannot be implemented outside of this crate.
+pub trait WideChar: Copy + Eq + Ord + Hash + fmt::Debug + private::Sealed {}
+
+impl WideChar for u16 {}
+impl WideChar for u32 {}
+
+mod private {
+	use core::char;
+
+	use super::DecodeWideError;
+
+	pub trait Sealed: Sized {
+		const NUL: Self;
+
+		/// Decodes the first character of the non-empty `units`, returning it
+		/// together with the number of code units it takes up.
+		fn decode_first(units: &[Self]) -> (Result<char, DecodeWideError>, usize);
+	}
+
+	impl Sealed for u16 {
+		const NUL: u16 = 0;
+
+		fn decode_first(units: &[u16]) -> (Result<char, DecodeWideError>, usize) {
+			match char::decode_utf16(units.iter().copied()).next() {
+				Some(Ok(c)) => (Ok(c), c.len_utf16()),
+				Some(Err(err)) => {
+					let unit = u32::from(err.unpaired_surrogate());
+					(Err(DecodeWideError { unit }), 1)
+				}
+				None => unreachable!(),
+			}
+		}
+	}
+
+	impl Sealed for u32 {
+		const NUL: u32 = 0;
+
+		fn decode_first(units: &[u32]) -> (Result<char, DecodeWideError>, usize) {
+			let unit = units[0];
+			(char::from_u32(unit).ok_or(DecodeWideError { unit }), 1)
+		}
+	}
+}
+
+/// An error returned when a wide string contains a code unit that does not
+/// form a valid character: an unpaired surrogate in UTF-16, or a surrogate
+/// or a value above `0x10FFFF` in UTF-32.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct DecodeWideError {
+	unit: u32,
+}
+
+impl DecodeWideError {
+	/// Returns the code unit that caused this error.
+	pub fn invalid_unit(&self) -> u32 {
+		self.unit
+	}
+}
+
+impl fmt::Display for DecodeWideError {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		write!(f, "invalid wide character code unit {:#X}", self.unit)
+	}
+}
+
+/// A borrowed nul-terminated wide string, as used by C APIs that take
+/// `wchar_t*`, `char16_t*` or `char32_t*` arguments.
+///
+/// The code unit type `C` is `u16` for UTF-16 and `u32` for UTF-32; use
+/// [`c_wchar`] for the `wchar_t` of the target. `WideCStr` is to a slice of
+/// code units as [`CStr`] is to [`OsStr`]: it is guaranteed to end with a
+/// single nul, which is not part of its contents.
+///
+/// [`CStr`]: crate::CStr
+/// [`OsStr`]: crate::OsStr
+///
+/// # Examples
+///
+/// ```
+/// use embedded_ffi::WideCStr;
+///
+/// let units = [0x45, 0x46, 0x1F600, 0];
+/// let s = WideCStr::<u32>::from_slice_with_nul(&units).unwrap();
+/// assert_eq!(s.len(), 3);
+/// assert!(s.chars().eq("EF\u{1F600}".chars().map(Ok)));
+/// ```
+#[derive(PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[repr(transparent)]
+pub struct WideCStr<C: WideChar> {
+	// The code units, including the terminating nul
+	inner: [C],
+}
+
+/// A borrowed nul-terminated UTF-16 string, as used by C APIs that take
+/// `CHAR16*` or 16-bit `wchar_t*` arguments.
+///
+/// # Examples
+///
+/// ```
+/// use embedded_ffi::WCStr;
+///
+/// let units = [0x45, 0x46, 0x49, 0];
+/// let s = WCStr::from_slice_with_nul(&units).unwrap();
+/// assert_eq!(s.as_slice(), [0x45, 0x46, 0x49]);
+/// assert_eq!(s.as_wstr().display().to_string(), "EFI");
+/// ```
+pub type WCStr = WideCStr<u16>;
+
+impl<C: WideChar> WideCStr<C> {
+	/// Wraps a raw nul-terminated wide string.
+	///
+	/// The length is found by scanning for the terminating nul, like
+	/// `wcslen` does.
+	///
+	/// # Safety
+	///
+	/// `ptr` must be non-null, aligned and point to a sequence of code units
+	/// that is terminated by a nul. The memory must stay valid and unchanged
+	/// for the lifetime `'a`.
+	pub unsafe fn from_ptr<'a>(ptr: *const C) -> &'a WideCStr<C> {
+		let mut len = 0;
+		while *ptr.add(len) != C::NUL {
+			len += 1;
+		}
+		WideCStr::from_slice_with_nul_unchecked(slice::from_raw_parts(ptr, len + 1))
+	}
+
+	/// Wraps a slice of code units that ends with a single nul.
+	///
+	/// # Errors
+	///
+	/// Returns an error if the slice does not end with a nul, or contains a
+	/// nul before its end.
+	pub fn from_slice_with_nul(slice: &[C]) -> Result<&WideCStr<C>, FromSliceWithNulError> {
+		match slice.iter().position(|&u| u == C::NUL) {
+			Some(pos) if pos + 1 == slice.len() => {
+				Ok(unsafe { WideCStr::from_slice_with_nul_unchecked(slice) })
+			}
+			Some(pos) => Err(FromSliceWithNulError::InteriorNul(pos)),
+			None => Err(FromSliceWithNulError::NotNulTerminated),
+		}
+	}
+
+	/// Wraps a slice of code units that ends with a single nul, without
+	/// checking it.
+	///
+	/// # Safety
+	///
+	/// The slice must end with a nul and must not contain any other nul.
+	pub unsafe fn from_slice_with_nul_unchecked(slice: &[C]) -> &WideCStr<C> {
+		&*(slice as *const [C] as *const WideCStr<C>)
+	}
+
+	/// Returns a raw pointer to the nul-terminated string, for passing it to
+	/// C.
+	pub fn as_ptr(&self) -> *const C {
+		self.inner.as_ptr()
+	}
+
+	/// Returns the code units of this string, without the terminating nul.
+	pub fn as_slice(&self) -> &[C] {
+		&self.inner[..self.inner.len() - 1]
+	}
+
+	/// Returns the code units of this string, including the terminating nul.
+	pub fn as_slice_with_nul(&self) -> &[C] {
+		&self.inner
+	}
+
+	/// Returns the length of this string in code units, without the
+	/// terminating nul.
+	pub fn len(&self) -> usize {
+		self.inner.len() - 1
+	}
+
+	/// Returns `true` if this string contains nothing but the terminating
+	/// nul.
+	pub fn is_empty(&self) -> bool {
+		self.len() == 0
+	}
+
+	/// Returns an iterator over the `char`s of this string.
+	///
+	/// Code units that do not form a valid character are returned as errors,
+	/// one per unit.
+	///
+	/// # Examples
+	///
+	/// ```
+	/// use embedded_ffi::WideCStr;
+	///
+	/// let units = [0x61, 0xD800, 0x110000, 0];
+	/// let s = WideCStr::<u32>::from_slice_with_nul(&units).unwrap();
+	/// let mut chars = s.chars();
+	/// assert_eq!(chars.next(), Some(Ok('a')));
+	/// assert_eq!(chars.next().unwrap().unwrap_err().invalid_unit(), 0xD800);
+	/// assert_eq!(chars.next().unwrap().unwrap_err().invalid_unit(), 0x110000);
+	/// assert_eq!(chars.next(), None);
+	/// ```
+	pub fn chars(&self) -> WideChars<'_, C> {
+		WideChars {
+			units: self.as_slice(),
+		}
+	}
+
+	/// Converts this string to an [`OsString`] of UTF-8 bytes.
+	///
+	/// # Errors
+	///
+	/// Returns the first code unit that does not form a valid character,
+	/// since it cannot be represented in UTF-8.
+	///
+	/// # Examples
+	///
+	/// ```
+	/// use embedded_ffi::{c_wchar, WideCStr};
+	///
+	/// let units: [c_wchar; 5] = [0x62, 0x6F, 0x6F, 0x74, 0];
+	/// let s = WideCStr::from_slice_with_nul(&units).unwrap();
+	/// assert_eq!(s.to_os_string().unwrap(), "boot");
+	/// ```
+	#[cfg(feature = "alloc")]
+	pub fn to_os_string(&self) -> Result<OsString, DecodeWideError> {
+		let mut os_string = OsString::with_capacity(self.len());
+		for c in self.chars() {
+			os_string.push(c?.encode_utf8(&mut [0; 4]));
+		}
+		Ok(os_string)
+	}
+
+	/// Converts this string to an [`OsString`] of UTF-8 bytes, replacing each
+	/// code unit that does not form a valid character with
+	/// [`U+FFFD REPLACEMENT CHARACTER`][U+FFFD].
+	///
+	/// [U+FFFD]: core::char::REPLACEMENT_CHARACTER
+	#[cfg(feature = "alloc")]
+	pub fn to_os_string_lossy(&self) -> OsString {
+		let mut os_string = OsString::with_capacity(self.len());
+		for c in self.chars() {
+			let c = c.unwrap_or(char::REPLACEMENT_CHARACTER);
+			os_string.push(c.encode_utf8(&mut [0; 4]));
+		}
+		os_string
+	}
+}
+
+impl WideCStr<u16> {
+	/// Returns the contents of this string as a [`WStr`], without the
+	/// terminating nul.
+	pub fn as_wstr(&self) -> &WStr {
+		WStr::from_slice(self.as_slice())
+	}
+}
+
+impl<C: WideChar> fmt::Debug for WideCStr<C> {
+	/// Formats the string like a `str`, with invalid code units written as
+	/// `\u{XXXX}` escapes.
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		f.write_char('"')?;
+		for c in self.chars() {
+			match c {
+				Ok('\'') => f.write_char('\'')?,
+				Ok(c) => {
+					for c in c.escape_debug() {
+						f.write_char(c)?;
+					}
+				}
+				Err(err) => write!(f, "\\u{{{:X}}}", err.invalid_unit())?,
+			}
+		}
+		f.write_char('"')
+	}
+}
+
+impl AsRef<WStr> for WideCStr<u16> {
+	fn as_ref(&self) -> &WStr {
+		self.as_wstr()
+	}
+}
+
+/// An iterator over the `char`s of a [`WideCStr`].
+///
+/// This `struct` is created by the [`chars`] method on [`WideCStr`].
+///
+/// [`chars`]: WideCStr::chars
+#[derive(Clone, Debug)]
+pub struct WideChars<'a, C: WideChar> {
+	units: &'a [C],
+}
+
+impl<C: WideChar> Iterator for WideChars<'_, C> {
+	type Item = Result<char, DecodeWideError>;
+
+	fn next(&mut self) -> Option<Result<char, DecodeWideError>> {
+		if self.units.is_empty() {
+			return None;
+		}
+		let (c, len) = C::decode_first(self.units);
+		self.units = &self.units[len..];
+		Some(c)
+	}
+}
+
+impl<C: WideChar> FusedIterator for WideChars<'_, C> {}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::OsStr;
+
+	#[test]
+	fn test_wcstr() {
+		let units = [0x61, 0x62, 0];
+		let s = WCStr::from_slice_with_nul(&units).unwrap();
+		assert_eq!(s.len(), 2);
+		assert_eq!(s.as_slice_with_nul(), units);
+		assert_eq!(unsafe { WCStr::from_ptr(units.as_ptr()) }, s);
+		assert!(WCStr::from_slice_with_nul(&[0]).unwrap().is_empty());
+		assert_eq!(
+			WCStr::from_slice_with_nul(&[0x61, 0, 0x62, 0]),
+			Err(FromSliceWithNulError::InteriorNul(1))
+		);
+		assert_eq!(
+			WCStr::from_slice_with_nul(&[0x61]),
+			Err(FromSliceWithNulError::NotNulTerminated)
+		);
+		assert_eq!(
+			WCStr::from_slice_with_nul(&[]),
+			Err(FromSliceWithNulError::NotNulTerminated)
+		);
+	}
+
+	#[test]
+	fn test_wide_cstr_u32() {
+		let units = [0x61, 0x1F600, 0xDC00, 0x110000, 0];
+		let s = WideCStr::<u32>::from_slice_with_nul(&units).unwrap();
+		assert_eq!(unsafe { WideCStr::from_ptr(units.as_ptr()) }, s);
+		assert_eq!(s.len(), 4);
+		let mut chars = s.chars();
+		assert_eq!(chars.next(), Some(Ok('a')));
+		assert_eq!(chars.next(), Some(Ok('\u{1F600}')));
+		assert_eq!(chars.next().unwrap().unwrap_err().invalid_unit(), 0xDC00);
+		assert_eq!(chars.next().unwrap().unwrap_err().invalid_unit(), 0x110000);
+		assert_eq!(chars.next(), None);
+		assert_eq!(
+			WideCStr::<u32>::from_slice_with_nul(&[0x61, 0, 0]),
+			Err(FromSliceWithNulError::InteriorNul(1))
+		);
+	}
+
+	#[test]
+	fn test_wide_cstr_u16_chars() {
+		let units = [0xD83D, 0xDE00, 0xD800, 0x61, 0xDE00, 0];
+		let s = WCStr::from_slice_with_nul(&units).unwrap();
+		let mut chars = s.chars();
+		assert_eq!(chars.next(), Some(Ok('\u{1F600}')));
+		assert_eq!(chars.next().unwrap().unwrap_err().invalid_unit(), 0xD800);
+		assert_eq!(chars.next(), Some(Ok('a')));
+		assert_eq!(chars.next().unwrap().unwrap_err().invalid_unit(), 0xDE00);
+		assert_eq!(chars.next(), None);
+	}
+
+	#[test]
+	fn test_wstr_chars() {
+		let units = [0x61, 0xD83D, 0xDE00, 0xDFFF, 0xD800];
+		let s = WStr::from_slice(&units);
+		let mut chars = s.chars();
+		assert_eq!(chars.next(), Some(Ok('a')));
+		assert_eq!(chars.next(), Some(Ok('\u{1F600}')));
+		assert_eq!(
+			chars.next().unwrap().unwrap_err().unpaired_surrogate(),
+			0xDFFF
+		);
+		assert_eq!(
+			chars.next().unwrap().unwrap_err().unpaired_surrogate(),
+			0xD800
+		);
+		assert_eq!(chars.next(), None);
+	}
+
+	#[test]
+	fn test_wtf8_round_trip() {
+		let units = [
+			0x61, 0xD800, 0xD83D, 0xDE00, 0xDFFF, 0xDBFF, 0x62, 0xDC00, 0xD800,
+		];
+		let mut buf = [0; 32];
+		let len = WStr::from_slice(&units).encode_wtf8_into(&mut buf).unwrap();
+		let bytes = &buf[..len];
+		assert_eq!(
+			bytes,
+			b"a\xED\xA0\x80\xF0\x9F\x98\x80\xED\xBF\xBF\xED\xAF\xBFb\xED\xB0\x80\xED\xA0\x80"
+		);
+		let os_str = OsStr::from_bytes_const(bytes);
+		assert_eq!(os_str.to_str(), None);
+		assert!(os_str
+			.decode_wtf8()
+			.map(Result::unwrap)
+			.eq(units.iter().copied()));
+		assert_eq!(
+			WStr::from_slice(&units).encode_wtf8_into(&mut buf[..len - 1]),
+			Err(CapacityError(()))
+		);
+	}
+
+	#[test]
+	fn test_wtf8_lossy() {
+		let os_str = OsStr::from_bytes_const(b"a\xED\xA0\x80b");
+		let mut chunks = os_str.utf8_chunks();
+		let chunk = chunks.next().unwrap();
+		assert_eq!((chunk.valid(), chunk.invalid()), ("a", &b"\xED"[..]));
+		assert_eq!(chunks.next().unwrap().invalid(), b"\xA0");
+		assert_eq!(chunks.next().unwrap().invalid(), b"\x80");
+		assert_eq!(chunks.next().unwrap().valid(), "b");
+		assert_eq!(chunks.next(), None);
+	}
+
+	#[test]
+	fn test_wtf8_invalid() {
+		let decode = |bytes| {
+			OsStr::from_bytes_const(bytes)
+				.decode_wtf8()
+				.find_map(Result::err)
+				.map(|err| err.valid_up_to())
+		};
+		assert_eq!(decode(b"ab\xED\xA0\x80\xED\xB0\x80"), Some(5));
+		assert_eq!(decode(b"a\xFF"), Some(1));
+		assert_eq!(decode(b"\xED\xA0"), Some(0));
+		assert_eq!(decode(b"\xED\xB0\x80\xED\xA0\x80"), None);
+		assert_eq!(decode(b"\xED\xA0\x80a\xED\xB0\x80"), None);
+
+		let mut units = OsStr::from_bytes_const(b"a\xFFb").decode_wtf8();
+		assert_eq!(units.next(), Some(Ok(0x61)));
+		assert!(units.next().unwrap().is_err());
+		assert_eq!(units.next(), None);
+	}
+
+	#[cfg(feature = "alloc")]
+	#[test]
+	fn test_wide_cstr_conversions() {
+		use alloc::format;
+
+		let units = [0x61, 0x1F600, 0xD800, 0x27, 0];
+		let s = WideCStr::<u32>::from_slice_with_nul(&units).unwrap();
+		assert_eq!(s.to_os_string().unwrap_err().invalid_unit(), 0xD800);
+		assert_eq!(s.to_os_string_lossy(), "a\u{1F600}\u{FFFD}'");
+		assert_eq!(format!("{:?}", s), "\"a\u{1F600}\\u{D800}'\"");
+	}
+
+	#[cfg(feature = "alloc")]
+	#[test]
+	fn test_wstr_conversions() {
+		use alloc::format;
+
+		let units = [0x61, 0xD83D, 0xDE00, 0xD800, 0x27];
+		let s = WStr::from_slice(&units);
+		assert_eq!(s.to_os_string().unwrap_err().unpaired_surrogate(), 0xD800);
+		assert_eq!(s.to_os_string_lossy(), "a\u{1F600}\u{FFFD}'");
+		assert_eq!(format!("{}", s.display()), "a\u{1F600}\u{FFFD}'");
+		assert_eq!(format!("{:*^8}", s.display()), "**a\u{1F600}\u{FFFD}'**");
+		assert_eq!(format!("{:>4.2}", s.display()), "  a\u{1F600}");
+		assert_eq!(format!("{:?}", s), "\"a\u{1F600}\\u{D800}'\"");
+
+		let wtf8 = s.to_os_string_wtf8();
+		assert_eq!(wtf8.as_bytes(), b"a\xF0\x9F\x98\x80\xED\xA0\x80'");
+		assert_eq!(WString::from_os_str_wtf8(&wtf8).unwrap().as_slice(), units);
+		assert_eq!(
+			WString::from_os_str_wtf8(OsStr::from_bytes(b"a\xC0"))
+				.unwrap_err()
+				.valid_up_to(),
+			1
+		);
+
+		let w = WString::from_os_str("a\u{1F600}").unwrap();
+		assert_eq!(w.as_slice(), &units[..3]);
+		assert_eq!(w.to_os_string().unwrap(), "a\u{1F600}");
+		assert!(WString::from_os_str(OsStr::from_bytes(b"\xFF")).is_err());
+		assert_eq!(
+			WString::from_os_str_lossy(OsStr::from_bytes(b"\xE2\x82\xFFa")).as_slice(),
+			[0xFFFD, 0xFFFD, 0x61]
+		);
+	}
+
+	#[cfg(feature = "alloc")]
+	#[test]
+	fn test_wtf8_display() {
+		use alloc::format;
+
+		let units = [0xDC00, 0x66, 0xD800, 0xDBFF, 0x2E, 0x74, 0xD83D, 0xDE00];
+		let name = WStr::from_slice(&units);
+		let wtf8 = name.to_os_string_wtf8();
+		let expected = "\u{FFFD}f\u{FFFD}\u{FFFD}.t\u{1F600}";
+		assert_eq!(format!("{}", name.display()), expected);
+		let decoded = WString::from_os_str_wtf8(&wtf8).unwrap();
+		assert_eq!(format!("{}", decoded.display()), expected);
+		assert_eq!(
+			format!("{}", wtf8.display()),
+			"\u{FFFD}\u{FFFD}\u{FFFD}f\u{FFFD}\u{FFFD}\u{FFFD}\u{FFFD}\u{FFFD}\u{FFFD}.t\u{1F600}"
+		);
+	}
+}